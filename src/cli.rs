@@ -0,0 +1,772 @@
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Duration;
+use clap::{Parser, Subcommand};
+use itertools::Itertools;
+
+use crate::{
+    db::{
+        backups::{self, BackupPolicy, DEFAULT_BACKUP_COUNT, DEFAULT_BACKUP_MAX_AGE_DAYS},
+        Backend, Database, JSONFileDatabase, JiraDatabase, DEFAULT_HISTORY_DEPTH,
+    },
+    error::{self, JiraError},
+    io_utils::get_user_input,
+    models::{DBState, Epic, Story},
+    project::{self, Config, Location, Overrides, CONFIG_FILE, PROJECT_DIR},
+    workflow::Category,
+};
+
+#[derive(Debug, Parser)]
+#[command(about = "A tiny Jira for your terminal")]
+pub struct Cli {
+    /// Where the board is stored: json or sqlite [default: json]
+    #[arg(long, env = "JIRA_BACKEND")]
+    pub backend: Option<Backend>,
+
+    /// Board file to open instead of the one found in `.jira/`
+    #[arg(long, env = "JIRA_DB")]
+    pub db: Option<PathBuf>,
+
+    /// Config file to use instead of `.jira/config.json`
+    #[arg(long, env = "JIRA_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// How many changes can be undone
+    #[arg(long, env = "JIRA_UNDO_DEPTH", default_value_t = DEFAULT_HISTORY_DEPTH)]
+    pub undo_depth: usize,
+
+    /// How many backups of the JSON board to keep, 0 turns them off
+    #[arg(long, env = "JIRA_KEEP_BACKUPS", default_value_t = DEFAULT_BACKUP_COUNT)]
+    pub keep_backups: usize,
+
+    /// Backups older than this many days are deleted
+    #[arg(long, env = "JIRA_BACKUP_MAX_AGE", default_value_t = DEFAULT_BACKUP_MAX_AGE_DAYS)]
+    pub backup_max_age: i64,
+
+    /// Your user id on the board, used for "my work" and as reporter of what you create
+    #[arg(long, env = "JIRA_USER")]
+    pub user: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create an empty board in `.jira/` under the current directory, or at `--db`
+    Init {
+        /// Also write `.jira/config.json` pointing at the new board
+        #[arg(long)]
+        with_config: bool,
+        /// Fill the board with a few example epics and stories
+        #[arg(long)]
+        sample: bool,
+        /// Replace an existing board. It is backed up first
+        #[arg(long)]
+        force: bool,
+    },
+    /// List or restore backups of the JSON board
+    Backups {
+        #[command(subcommand)]
+        command: BackupsCommand,
+    },
+    /// List or add the people epics and stories can be assigned to
+    Users {
+        #[command(subcommand)]
+        command: UsersCommand,
+    },
+    /// Check the board for broken references between epics and stories
+    Fsck {
+        /// Fix every problem that can be fixed without losing data
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UsersCommand {
+    /// Show every user on the board
+    List,
+    /// Add a user
+    Add {
+        /// Short id items refer to the user by, e.g. jdoe
+        id: String,
+        /// Full name
+        name: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BackupsCommand {
+    /// List backups, newest first
+    List,
+    /// Preview a backup and put it back in place of the current board
+    Restore {
+        /// Backup file name, as printed by `backups list`
+        name: String,
+        /// Restore without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+impl Cli {
+    pub fn location(&self) -> error::Result<Location> {
+        let overrides = Overrides {
+            backend: self.backend,
+            database: self.db.clone(),
+            config: self.config.clone(),
+        };
+
+        project::resolve(overrides, &env::current_dir()?)
+    }
+
+    pub fn backup_policy(&self) -> BackupPolicy {
+        BackupPolicy {
+            keep: self.keep_backups,
+            max_age: Some(Duration::days(self.backup_max_age)),
+        }
+    }
+
+    pub fn open_database(&self, location: &Location) -> error::Result<JiraDatabase> {
+        if !location.exists() {
+            return Err(JiraError::NoBoard(location.file_path.clone()));
+        }
+
+        let db = match location.backend {
+            Backend::Json => {
+                let database = JSONFileDatabase::new(location.file_path.clone())?
+                    .with_backups(self.backup_policy());
+                JiraDatabase::new(Box::new(database))
+            }
+            Backend::Sqlite => JiraDatabase::open(location.backend, location.file_path.clone())?,
+        };
+
+        Ok(db
+            .with_history_depth(self.undo_depth)
+            .with_workflows(location.workflows.clone())
+            .with_estimate_scale(location.estimate_scale.clone()))
+    }
+}
+
+pub fn run_init(cli: &Cli, with_config: bool, sample: bool, force: bool) -> Result<()> {
+    let cwd = env::current_dir()?;
+    let location = project::init_location(cli.backend, cli.db.clone(), &cwd);
+
+    init(&location, &cwd, with_config, sample, force)?;
+
+    println!("created a board at {}", location.file_path);
+    Ok(())
+}
+
+fn init(
+    location: &Location,
+    cwd: &Path,
+    with_config: bool,
+    sample: bool,
+    force: bool,
+) -> Result<()> {
+    let config_path = cwd.join(PROJECT_DIR).join(CONFIG_FILE);
+    // kept to be put back if the board cannot be created
+    let previous_config = match fs::read(&config_path) {
+        Ok(content) => Some(content),
+        Err(error) if error.kind() == ErrorKind::NotFound => None,
+        Err(error) => return Err(error.into()),
+    };
+    if with_config && previous_config.is_some() && !force {
+        bail!(
+            "there is already a config at {}, use --force to replace it",
+            config_path.display()
+        );
+    }
+    // the project's workflows and scale outlive a new board, and the sample has to follow them
+    let existing = match previous_config {
+        Some(_) => Config::load(&config_path)?,
+        None => Config::default(),
+    };
+
+    // the config goes first, a board without its config would block the next `init`
+    if with_config {
+        let board = Path::new(&location.file_path);
+        // a board inside `.jira/` is referenced by name so the project can be moved around
+        let database = match board.parent() {
+            Some(dir) if dir == config_path.parent().unwrap_or(cwd) => {
+                board.file_name().map(PathBuf::from)
+            }
+            _ => Some(board.to_path_buf()),
+        };
+        let config = Config {
+            backend: Some(location.backend),
+            database,
+            workflows: existing.workflows.clone(),
+            estimate_scale: existing.estimate_scale.clone(),
+        };
+        if let Some(dir) = config_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&config_path, serde_json::to_string_pretty(&config)?)?;
+    }
+
+    let created = JiraDatabase::create(location.backend, location.file_path.clone(), force);
+    if let Err(error) = created {
+        if with_config {
+            let _ = match &previous_config {
+                Some(content) => fs::write(&config_path, content),
+                None => fs::remove_file(&config_path),
+            };
+        }
+        return Err(match error {
+            JiraError::BoardExists(_) => anyhow!("{}, use --force to replace it", error),
+            error => anyhow!(error),
+        });
+    }
+
+    if sample {
+        // no undo history for the sample, it is the starting point
+        let db = JiraDatabase::open(location.backend, location.file_path.clone())?
+            .with_history_depth(0)
+            .with_workflows(existing.workflows.unwrap_or_default());
+        add_sample(&db)?;
+    }
+
+    Ok(())
+}
+
+fn add_sample(db: &JiraDatabase) -> error::Result<()> {
+    let epic_id = db.create_epic(Epic::new(
+        "Getting started".to_string(),
+        "A tour of the board, feel free to delete it".to_string(),
+    ))?;
+    db.create_story(
+        Story::new(
+            "Open this story".to_string(),
+            "Type its id on the epic page".to_string(),
+        ),
+        epic_id,
+    )?;
+    let story_id = db.create_story(
+        Story::new(
+            "Update a status".to_string(),
+            "Press [u] on a story or an epic".to_string(),
+        ),
+        epic_id,
+    )?;
+    let workflow = &db.workflows().story;
+    let started = workflow
+        .targets(&workflow.initial)
+        .into_iter()
+        .find(|state| state.category == Category::InProgress)
+        .map(|state| state.id.clone());
+    if let Some(status) = started {
+        db.update_story_status(story_id, status)?;
+    }
+    db.create_story(
+        Story::new(
+            "Undo a change".to_string(),
+            "Press [z] to undo and [y] to redo".to_string(),
+        ),
+        epic_id,
+    )?;
+
+    let epic_id = db.create_epic(Epic::new(
+        "Your first project".to_string(),
+        "Add the stories you are working on".to_string(),
+    ))?;
+    db.create_story(
+        Story::new(
+            "Create a story".to_string(),
+            "Press [c] on an epic page".to_string(),
+        ),
+        epic_id,
+    )?;
+
+    Ok(())
+}
+
+// asks before creating an empty board where none exists yet. returns whether there is one now
+pub fn offer_to_create(location: &Location) -> Result<bool> {
+    if location.exists() {
+        return Ok(true);
+    }
+
+    println!(
+        "There is no board at {}. Create an empty one? [Y/n]: ",
+        location.file_path
+    );
+    if !get_user_input().eq("Y") {
+        return Ok(false);
+    }
+
+    JiraDatabase::create(location.backend, location.file_path.clone(), false)
+        .with_context(|| anyhow!("failed to create {}", location.file_path))?;
+    Ok(true)
+}
+
+// prints what is wrong with the board and returns whether it is clean now
+pub fn run_fsck(cli: &Cli, location: &Location, repair: bool) -> Result<bool> {
+    let db = cli
+        .open_database(location)
+        .with_context(|| anyhow!("failed to open {}", location.file_path))?;
+    let (lines, clean) = fsck(&db, repair)?;
+
+    if lines.is_empty() {
+        println!("no problems found in {}", location.file_path);
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+
+    Ok(clean)
+}
+
+fn fsck(db: &JiraDatabase, repair: bool) -> Result<(Vec<String>, bool)> {
+    let mut lines = vec![];
+
+    if repair {
+        for problem in db.repair().with_context(|| anyhow!("failed to repair!"))? {
+            lines.push(format!("fixed: {}", problem));
+        }
+    }
+
+    let problems = db.check()?;
+    for problem in &problems {
+        if problem.is_repairable() {
+            lines.push(format!("{} (run with --repair to fix)", problem));
+        } else {
+            lines.push(format!("{} (needs a manual fix)", problem));
+        }
+    }
+
+    Ok((lines, problems.is_empty()))
+}
+
+pub fn run_users(cli: &Cli, location: &Location, command: &UsersCommand) -> Result<()> {
+    let db = cli
+        .open_database(location)
+        .with_context(|| anyhow!("failed to open {}", location.file_path))?;
+
+    match command {
+        UsersCommand::List => {
+            let lines = list_users(&db)?;
+            if lines.is_empty() {
+                println!("no users yet, add one with `users add <id> <name>`");
+            }
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        UsersCommand::Add { id, name } => {
+            db.add_user(id.clone(), name.clone())
+                .with_context(|| anyhow!("failed to add user {}", id))?;
+            println!("added {}", id);
+        }
+    }
+
+    Ok(())
+}
+
+fn list_users(db: &JiraDatabase) -> Result<Vec<String>> {
+    Ok(db
+        .read_db()?
+        .users
+        .iter()
+        .sorted_by_key(|(id, _)| *id)
+        .map(|(id, user)| format!("{} | {}", id, user.name))
+        .collect())
+}
+
+pub fn run_backups(cli: &Cli, location: &Location, command: &BackupsCommand) -> Result<()> {
+    if location.backend != Backend::Json {
+        bail!("backups are only kept for the json backend");
+    }
+
+    let db = JSONFileDatabase::new(location.file_path.clone())
+        .with_context(|| anyhow!("failed to open {}", location.file_path))?
+        .with_backups(cli.backup_policy());
+
+    match command {
+        BackupsCommand::List => {
+            let lines = list_backups(&db)?;
+            if lines.is_empty() {
+                println!("no backups of {} yet", db.file_path);
+            }
+            for line in lines {
+                println!("{}", line);
+            }
+        }
+        BackupsCommand::Restore { name, yes } => {
+            let confirm = |preview: &str| {
+                println!("{}", preview);
+                if *yes {
+                    return true;
+                }
+                println!("Restore this backup? The current board is backed up first [Y/n]: ");
+                get_user_input().eq("Y")
+            };
+
+            if restore_backup(&db, name, &confirm)? {
+                println!("restored {}", name);
+            } else {
+                println!("nothing restored");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn list_backups(db: &JSONFileDatabase) -> Result<Vec<String>> {
+    Ok(db
+        .backups()?
+        .iter()
+        .map(|backup| {
+            format!(
+                "{} | {} | {}",
+                backup.name(),
+                backup.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                describe(backup.read())
+            )
+        })
+        .collect())
+}
+
+// returns whether the backup was put in place
+fn restore_backup(
+    db: &JSONFileDatabase,
+    name: &str,
+    confirm: &dyn Fn(&str) -> bool,
+) -> Result<bool> {
+    let backup = backups::find(&db.file_path, name)?;
+    let backup_state = backup
+        .read()
+        .with_context(|| anyhow!("backup {} is unreadable", name))?;
+    let current_state = db.read_db();
+
+    let preview = format!(
+        "backup:  {}\ncurrent: {}",
+        describe(Ok(backup_state)),
+        describe(current_state)
+    );
+
+    if !confirm(&preview) {
+        return Ok(false);
+    }
+
+    db.restore(&backup)?;
+    Ok(true)
+}
+
+fn describe(db_state: std::result::Result<DBState, JiraError>) -> String {
+    match db_state {
+        Ok(db_state) => format!(
+            "{} epics, {} stories",
+            db_state.epics.len(),
+            db_state.stories.len()
+        ),
+        Err(error) => format!("unreadable ({})", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::db::test_utils::MockDB;
+
+    #[test]
+    fn cli_should_be_well_formed() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn cli_should_parse_users_add() {
+        let cli = Cli::parse_from([
+            "cli_jira", "--user", "jdoe", "users", "add", "rroe", "R. Roe",
+        ]);
+
+        assert_eq!(cli.user.as_deref(), Some("jdoe"));
+        assert!(matches!(
+            cli.command,
+            Some(Command::Users {
+                command: UsersCommand::Add { ref id, ref name }
+            }) if id == "rroe" && name == "R. Roe"
+        ));
+    }
+
+    #[test]
+    fn list_users_should_sort_by_id() {
+        let db = JiraDatabase::new(Box::new(MockDB::new()));
+        db.add_user("rroe".to_string(), "Richard Roe".to_string())
+            .unwrap();
+        db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+            .unwrap();
+
+        assert_eq!(
+            list_users(&db).unwrap(),
+            vec!["jdoe | Jane Doe", "rroe | Richard Roe"]
+        );
+    }
+
+    #[test]
+    fn cli_should_parse_restore() {
+        let cli = Cli::parse_from(["cli_jira", "backups", "restore", "db.json.x.bak", "--yes"]);
+
+        assert!(matches!(
+            cli.command,
+            Some(Command::Backups {
+                command: BackupsCommand::Restore { ref name, yes: true }
+            }) if name == "db.json.x.bak"
+        ));
+    }
+
+    #[test]
+    fn list_backups_should_show_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = board_with_backup(&dir);
+
+        let lines = list_backups(&db).unwrap();
+
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].ends_with("| 0 epics, 0 stories"));
+    }
+
+    #[test]
+    fn restore_backup_should_do_nothing_unless_confirmed() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = board_with_backup(&dir);
+        let name = db.backups().unwrap()[0].name();
+
+        let restored = restore_backup(&db, &name, &|preview| {
+            assert_eq!(
+                preview,
+                "backup:  0 epics, 0 stories\ncurrent: 1 epics, 0 stories"
+            );
+            false
+        })
+        .unwrap();
+
+        assert!(!restored);
+        assert_eq!(db.read_db().unwrap().epics.len(), 1);
+    }
+
+    #[test]
+    fn restore_backup_should_replace_board_and_back_it_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = board_with_backup(&dir);
+        let name = db.backups().unwrap()[0].name();
+
+        assert!(restore_backup(&db, &name, &|_| true).unwrap());
+
+        assert_eq!(db.read_db().unwrap().epics.len(), 0);
+        let newest = &db.backups().unwrap()[0];
+        assert_eq!(newest.read().unwrap().epics.len(), 1);
+
+        let db = JiraDatabase::open(Backend::Json, db.file_path.clone()).unwrap();
+        assert!(matches!(db.undo(), Err(JiraError::NothingToUndo)));
+    }
+
+    #[test]
+    fn restore_backup_should_work_over_corrupt_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = board_with_backup(&dir);
+        let name = db.backups().unwrap()[0].name();
+        fs::write(&db.file_path, "{ not json").unwrap();
+
+        assert!(restore_backup(&db, &name, &|_| true).unwrap());
+
+        assert_eq!(db.read_db().unwrap().epics.len(), 0);
+    }
+
+    #[test]
+    fn fsck_should_report_then_repair() {
+        let db = JiraDatabase::new(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let mut db_state = db.read_db().unwrap();
+        db_state.epics.get_mut(&epic_id).unwrap().stories.push(99);
+        db_state
+            .stories
+            .insert(7, Story::new("".to_string(), "".to_string()));
+        db_state.last_item_id = 7;
+        db.database.write_db(&db_state).unwrap();
+
+        let (lines, clean) = fsck(&db, false).unwrap();
+        assert!(!clean);
+        assert_eq!(
+            lines,
+            vec![
+                "epic 1 lists story 99, which does not exist (run with --repair to fix)",
+                "story 7 does not belong to any epic (needs a manual fix)",
+            ]
+        );
+
+        let (lines, clean) = fsck(&db, true).unwrap();
+        assert!(!clean);
+        assert_eq!(
+            lines,
+            vec![
+                "fixed: epic 1 lists story 99, which does not exist",
+                "story 7 does not belong to any epic (needs a manual fix)",
+            ]
+        );
+    }
+
+    #[test]
+    fn init_should_create_board_and_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = project::init_location(None, None, dir.path());
+
+        init(&location, dir.path(), true, false, false).unwrap();
+
+        let db = JiraDatabase::open(location.backend, location.file_path.clone()).unwrap();
+        assert_eq!(db.read_db().unwrap().last_item_id, 0);
+        assert_eq!(
+            Config::load(&dir.path().join(".jira/config.json")).unwrap(),
+            Config {
+                backend: Some(Backend::Json),
+                database: Some(PathBuf::from("db.json")),
+                workflows: None,
+                estimate_scale: None,
+            }
+        );
+        assert_eq!(
+            project::resolve(Overrides::default(), dir.path()).unwrap(),
+            location
+        );
+    }
+
+    #[test]
+    fn init_should_create_config_dir_for_board_elsewhere() {
+        let dir = tempfile::tempdir().unwrap();
+        let board = dir.path().join("board.json");
+        let location = project::init_location(None, Some(PathBuf::from("board.json")), dir.path());
+
+        init(&location, dir.path(), true, false, false).unwrap();
+
+        assert!(board.exists());
+        assert_eq!(
+            Config::load(&dir.path().join(".jira/config.json"))
+                .unwrap()
+                .database,
+            Some(board)
+        );
+        assert_eq!(
+            project::resolve(Overrides::default(), dir.path()).unwrap(),
+            location
+        );
+    }
+
+    #[test]
+    fn init_should_add_sample_without_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = project::init_location(Some(Backend::Sqlite), None, dir.path());
+
+        init(&location, dir.path(), false, true, false).unwrap();
+
+        let db = JiraDatabase::open(location.backend, location.file_path.clone()).unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics.len(), 2);
+        assert_eq!(db_state.stories.len(), 4);
+        assert!(db.check().unwrap().is_empty());
+        assert!(db.undo().is_err());
+    }
+
+    #[test]
+    fn init_should_refuse_existing_board_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = project::init_location(None, None, dir.path());
+        init(&location, dir.path(), false, true, false).unwrap();
+
+        let error = init(&location, dir.path(), false, false, false).unwrap_err();
+        assert!(error.to_string().contains("use --force"));
+
+        init(&location, dir.path(), false, false, true).unwrap();
+        let db = JiraDatabase::open(location.backend, location.file_path.clone()).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+    }
+
+    #[test]
+    fn init_should_keep_config_when_forced_init_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".jira/config.json");
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        let config = r#"{ "database": "team.json", "estimate_scale": [1, 2, 4] }"#;
+        fs::write(&config_path, config).unwrap();
+        // a directory in place of the board cannot be backed up and replaced
+        fs::create_dir_all(dir.path().join("board.json")).unwrap();
+        let location = project::init_location(None, Some(PathBuf::from("board.json")), dir.path());
+
+        assert!(init(&location, dir.path(), true, false, true).is_err());
+
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), config);
+    }
+
+    #[test]
+    fn init_should_keep_workflows_and_scale_of_replaced_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join(".jira/config.json");
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::write(
+            &config_path,
+            r#"{ "database": "old.json", "estimate_scale": [1, 2, 4], "workflows": { "story": {
+                "initial": "todo",
+                "states": [
+                    { "id": "todo", "name": "To Do", "category": "todo" },
+                    { "id": "doing", "name": "Doing", "category": "in_progress" },
+                    { "id": "done", "name": "Done", "category": "done" }
+                ]
+            } } }"#,
+        )
+        .unwrap();
+        let location = project::init_location(None, None, dir.path());
+
+        init(&location, dir.path(), true, true, true).unwrap();
+
+        let config = Config::load(&config_path).unwrap();
+        assert_eq!(config.database, Some(PathBuf::from("db.json")));
+        assert!(config.estimate_scale.is_some());
+        let workflows = config.workflows.unwrap();
+        assert_eq!(workflows.story.initial.to_string(), "todo");
+
+        let location = project::resolve(Overrides::default(), dir.path()).unwrap();
+        let db = JiraDatabase::open(location.backend, location.file_path.clone())
+            .unwrap()
+            .with_workflows(location.workflows);
+        let db_state = db.read_db().unwrap();
+        let mut statuses = db_state
+            .stories
+            .values()
+            .map(|story| story.status.to_string())
+            .collect::<Vec<_>>();
+        statuses.sort();
+        statuses.dedup();
+        assert_eq!(statuses, vec!["doing", "todo"]);
+    }
+
+    // an empty board, backed up once before an epic was added to it
+    fn board_with_backup(dir: &tempfile::TempDir) -> JSONFileDatabase {
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_string();
+        JiraDatabase::create(Backend::Json, file_path.clone(), false).unwrap();
+
+        let db = JiraDatabase::new(Box::new(
+            JSONFileDatabase::new(file_path.clone())
+                .unwrap()
+                .with_backups(BackupPolicy::default()),
+        ));
+        db.create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+
+        JSONFileDatabase::new(file_path)
+            .unwrap()
+            .with_backups(BackupPolicy::default())
+    }
+}
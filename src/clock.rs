@@ -0,0 +1,47 @@
+use std::cell::Cell;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+// where `JiraDatabase` gets the time it stamps on items from, so tests can pin it
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// stands still until it is moved on with `advance`
+pub struct FixedClock {
+    now: Cell<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Cell::new(now),
+        }
+    }
+
+    // bc test util
+    #[allow(dead_code)]
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for FixedClock {
+    fn default() -> Self {
+        Self::new(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap())
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.now.get()
+    }
+}
@@ -0,0 +1,294 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+
+use super::json_file::{decode_db, write_atomically};
+use crate::{
+    error::{JiraError, Result},
+    models::DBState,
+};
+
+pub const DEFAULT_BACKUP_COUNT: usize = 10;
+pub const DEFAULT_BACKUP_MAX_AGE_DAYS: i64 = 30;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.6fZ";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupPolicy {
+    // how many snapshots are kept, 0 turns backups off
+    pub keep: usize,
+    // snapshots older than this are pruned even when there is room for them
+    pub max_age: Option<Duration>,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            keep: DEFAULT_BACKUP_COUNT,
+            max_age: Some(Duration::days(DEFAULT_BACKUP_MAX_AGE_DAYS)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Backup {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn read(&self) -> Result<DBState> {
+        decode_db(&fs::read_to_string(&self.path)?)
+    }
+}
+
+// snapshots live in a `backups` directory next to the database file
+pub fn backups_dir(file_path: &str) -> PathBuf {
+    match Path::new(file_path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.join("backups"),
+        _ => PathBuf::from("backups"),
+    }
+}
+
+fn file_name(file_path: &str) -> String {
+    Path::new(file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+// copies the database as it is on disk before it gets overwritten, then prunes old snapshots
+pub fn create(file_path: &str, policy: &BackupPolicy, now: DateTime<Utc>) -> Result<()> {
+    if policy.keep == 0 {
+        return Ok(());
+    }
+
+    let db_content = match fs::read(file_path) {
+        Ok(db_content) => db_content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let dir = backups_dir(file_path);
+    fs::create_dir_all(&dir)?;
+    // `restore` counts on these after a crash, so they are written like the board itself
+    let path = dir.join(format!(
+        "{}.{}.bak",
+        file_name(file_path),
+        now.format(TIMESTAMP_FORMAT)
+    ));
+    let temp_path = PathBuf::from(format!("{}.tmp", path.display()));
+    write_atomically(&path, &temp_path, &db_content)?;
+
+    prune(file_path, policy, now)
+}
+
+// newest first
+pub fn list(file_path: &str) -> Result<Vec<Backup>> {
+    let entries = match fs::read_dir(backups_dir(file_path)) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(error) => return Err(error.into()),
+    };
+    let prefix = format!("{}.", file_name(file_path));
+
+    let mut backups = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // anything we did not write ourselves is left alone
+        let timestamp = match name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".bak"))
+        {
+            Some(timestamp) => timestamp,
+            None => continue,
+        };
+
+        if let Ok(created_at) = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT) {
+            backups.push(Backup {
+                path,
+                created_at: created_at.and_utc(),
+            });
+        }
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+pub fn find(file_path: &str, name: &str) -> Result<Backup> {
+    list(file_path)?
+        .into_iter()
+        .find(|backup| backup.name() == name)
+        .ok_or_else(|| JiraError::BackupNotFound(name.to_string()))
+}
+
+fn prune(file_path: &str, policy: &BackupPolicy, now: DateTime<Utc>) -> Result<()> {
+    for (index, backup) in list(file_path)?.into_iter().enumerate() {
+        let too_many = index >= policy.keep;
+        let too_old = policy
+            .max_age
+            .is_some_and(|max_age| now - backup.created_at > max_age);
+
+        if too_many || too_old {
+            fs::remove_file(&backup.path)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_should_copy_current_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = db_path_in(&dir);
+        fs::write(&file_path, "original").unwrap();
+
+        create(&file_path, &BackupPolicy::default(), at(0)).unwrap();
+
+        let backups = list(&file_path).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].created_at, at(0));
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "original");
+        assert!(backups[0].path.starts_with(dir.path().join("backups")));
+    }
+
+    #[test]
+    fn create_should_not_leave_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = db_path_in(&dir);
+        fs::write(&file_path, "original").unwrap();
+
+        create(&file_path, &BackupPolicy::default(), at(0)).unwrap();
+
+        let names = fs::read_dir(dir.path().join("backups"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![list(&file_path).unwrap()[0].name()]);
+    }
+
+    #[test]
+    fn create_should_skip_missing_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = db_path_in(&dir);
+
+        create(&file_path, &BackupPolicy::default(), at(0)).unwrap();
+
+        assert!(list(&file_path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn create_should_do_nothing_when_disabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = db_path_in(&dir);
+        fs::write(&file_path, "original").unwrap();
+        let policy = BackupPolicy {
+            keep: 0,
+            max_age: None,
+        };
+
+        create(&file_path, &policy, at(0)).unwrap();
+
+        assert!(!backups_dir(&file_path).exists());
+    }
+
+    #[test]
+    fn create_should_prune_by_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = db_path_in(&dir);
+        fs::write(&file_path, "original").unwrap();
+        let policy = BackupPolicy {
+            keep: 2,
+            max_age: None,
+        };
+
+        for minute in 0..4 {
+            create(&file_path, &policy, at(minute)).unwrap();
+        }
+
+        let created: Vec<_> = list(&file_path)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.created_at)
+            .collect();
+        assert_eq!(created, vec![at(3), at(2)]);
+    }
+
+    #[test]
+    fn create_should_prune_by_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = db_path_in(&dir);
+        fs::write(&file_path, "original").unwrap();
+        let policy = BackupPolicy {
+            keep: 10,
+            max_age: Some(Duration::minutes(30)),
+        };
+
+        create(&file_path, &policy, at(0)).unwrap();
+        create(&file_path, &policy, at(20)).unwrap();
+        create(&file_path, &policy, at(45)).unwrap();
+
+        let created: Vec<_> = list(&file_path)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.created_at)
+            .collect();
+        assert_eq!(created, vec![at(45), at(20)]);
+    }
+
+    #[test]
+    fn list_should_ignore_foreign_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = db_path_in(&dir);
+        fs::create_dir_all(backups_dir(&file_path)).unwrap();
+        fs::write(backups_dir(&file_path).join("notes.txt"), "").unwrap();
+        fs::write(backups_dir(&file_path).join("db.json.garbage.bak"), "").unwrap();
+        fs::write(
+            backups_dir(&file_path).join("other.json.20240101T000000.000000Z.bak"),
+            "",
+        )
+        .unwrap();
+
+        assert!(list(&file_path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn find_should_fail_for_unknown_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = db_path_in(&dir);
+
+        assert!(matches!(
+            find(&file_path, "missing.bak"),
+            Err(JiraError::BackupNotFound(_))
+        ));
+    }
+
+    fn db_path_in(dir: &tempfile::TempDir) -> String {
+        dir.path().join("db.json").to_str().unwrap().to_string()
+    }
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::minutes(minute)
+    }
+}
@@ -0,0 +1,369 @@
+use std::{collections::HashMap, fmt};
+
+use itertools::Itertools;
+
+use super::labels;
+use crate::models::DBState;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    // an epic lists a story that does not exist
+    MissingStory { epic_id: u32, story_id: u32 },
+    // an epic lists the same story more than once
+    DuplicateStory { epic_id: u32, story_id: u32 },
+    // a story is listed by several epics. `epic_ids` is sorted, the first one keeps it on repair
+    SharedStory { story_id: u32, epic_ids: Vec<u32> },
+    // a story no epic points to
+    OrphanStory { story_id: u32 },
+    // an epic and a story were given the same id
+    IdClash { id: u32 },
+    // new items would be handed ids that are already in use
+    StaleLastItemId { last_item_id: u32, max_id: u32 },
+    // the label index does not match the labels on the items
+    StaleLabelIndex,
+    // comments are kept for a story that does not exist
+    StrayComments { story_id: u32 },
+    // a link goes out of or points at an item that does not exist
+    DanglingLink { from: u32, to: u32 },
+}
+
+impl Problem {
+    // whether `repair` can fix it without guessing what the user meant
+    pub fn is_repairable(&self) -> bool {
+        !matches!(self, Problem::OrphanStory { .. } | Problem::IdClash { .. })
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::MissingStory { epic_id, story_id } => {
+                write!(
+                    f,
+                    "epic {epic_id} lists story {story_id}, which does not exist"
+                )
+            }
+            Problem::DuplicateStory { epic_id, story_id } => {
+                write!(f, "epic {epic_id} lists story {story_id} more than once")
+            }
+            Problem::SharedStory { story_id, epic_ids } => write!(
+                f,
+                "story {story_id} belongs to several epics: {}",
+                epic_ids.iter().join(", ")
+            ),
+            Problem::OrphanStory { story_id } => {
+                write!(f, "story {story_id} does not belong to any epic")
+            }
+            Problem::IdClash { id } => write!(f, "id {id} is used by both an epic and a story"),
+            Problem::StaleLastItemId {
+                last_item_id,
+                max_id,
+            } => write!(
+                f,
+                "last_item_id is {last_item_id}, but ids up to {max_id} are in use"
+            ),
+            Problem::StaleLabelIndex => {
+                write!(f, "the label index does not match the labels on the items")
+            }
+            Problem::StrayComments { story_id } => {
+                write!(
+                    f,
+                    "comments are kept for story {story_id}, which does not exist"
+                )
+            }
+            Problem::DanglingLink { from, to } => {
+                write!(
+                    f,
+                    "{from} is linked to {to}, but one of them does not exist"
+                )
+            }
+        }
+    }
+}
+
+pub fn check(db_state: &DBState) -> Vec<Problem> {
+    let mut problems = vec![];
+    let mut owners: HashMap<u32, Vec<u32>> = HashMap::new();
+
+    for epic_id in db_state.epics.keys().sorted() {
+        let epic = &db_state.epics[epic_id];
+
+        for (index, story_id) in epic.stories.iter().enumerate() {
+            if epic.stories[..index].contains(story_id) {
+                problems.push(Problem::DuplicateStory {
+                    epic_id: *epic_id,
+                    story_id: *story_id,
+                });
+            } else if db_state.stories.contains_key(story_id) {
+                owners.entry(*story_id).or_default().push(*epic_id);
+            } else {
+                problems.push(Problem::MissingStory {
+                    epic_id: *epic_id,
+                    story_id: *story_id,
+                });
+            }
+        }
+    }
+
+    for story_id in db_state.stories.keys().sorted() {
+        match owners.get(story_id) {
+            None => problems.push(Problem::OrphanStory {
+                story_id: *story_id,
+            }),
+            Some(epic_ids) if epic_ids.len() > 1 => problems.push(Problem::SharedStory {
+                story_id: *story_id,
+                epic_ids: epic_ids.clone(),
+            }),
+            Some(_) => {}
+        }
+
+        if db_state.epics.contains_key(story_id) {
+            problems.push(Problem::IdClash { id: *story_id });
+        }
+    }
+
+    let max_id = db_state
+        .epics
+        .keys()
+        .chain(db_state.stories.keys())
+        .copied()
+        .max()
+        .unwrap_or(0);
+    if db_state.last_item_id < max_id {
+        problems.push(Problem::StaleLastItemId {
+            last_item_id: db_state.last_item_id,
+            max_id,
+        });
+    }
+
+    if db_state.labels != labels::index(db_state) {
+        problems.push(Problem::StaleLabelIndex);
+    }
+
+    for story_id in db_state.comments.keys().sorted() {
+        if !db_state.stories.contains_key(story_id) {
+            problems.push(Problem::StrayComments {
+                story_id: *story_id,
+            });
+        }
+    }
+
+    let is_item = |id: &u32| db_state.epics.contains_key(id) || db_state.stories.contains_key(id);
+    for from in db_state.links.keys().sorted() {
+        for link in &db_state.links[from] {
+            if !is_item(from) || !is_item(&link.to) {
+                problems.push(Problem::DanglingLink {
+                    from: *from,
+                    to: link.to,
+                });
+            }
+        }
+    }
+
+    problems
+}
+
+// fixes every repairable problem in place and returns the ones that were fixed
+pub fn repair(db_state: &mut DBState) -> Vec<Problem> {
+    let problems: Vec<Problem> = check(db_state)
+        .into_iter()
+        .filter(Problem::is_repairable)
+        .collect();
+
+    for problem in &problems {
+        match problem {
+            Problem::MissingStory { epic_id, story_id } => {
+                if let Some(epic) = db_state.epics.get_mut(epic_id) {
+                    epic.stories.retain(|id| id != story_id);
+                }
+            }
+            Problem::DuplicateStory { epic_id, story_id } => {
+                if let Some(epic) = db_state.epics.get_mut(epic_id) {
+                    let first = epic.stories.iter().position(|id| id == story_id);
+                    let mut index = 0;
+                    epic.stories.retain(|id| {
+                        let keep = id != story_id || Some(index) == first;
+                        index += 1;
+                        keep
+                    });
+                }
+            }
+            Problem::SharedStory { story_id, epic_ids } => {
+                for epic_id in &epic_ids[1..] {
+                    if let Some(epic) = db_state.epics.get_mut(epic_id) {
+                        epic.stories.retain(|id| id != story_id);
+                    }
+                }
+            }
+            Problem::StaleLastItemId { max_id, .. } => db_state.last_item_id = *max_id,
+            Problem::StaleLabelIndex => db_state.labels = labels::index(db_state),
+            Problem::StrayComments { story_id } => {
+                db_state.comments.remove(story_id);
+            }
+            Problem::DanglingLink { from, to } => {
+                if let Some(links) = db_state.links.get_mut(from) {
+                    links.retain(|link| link.to != *to);
+                    if links.is_empty() {
+                        db_state.links.remove(from);
+                    }
+                }
+            }
+            Problem::OrphanStory { .. } | Problem::IdClash { .. } => {}
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::Utc;
+
+    use super::*;
+    use crate::models::{Comment, Epic, Link, LinkKind, Story};
+
+    #[test]
+    fn check_should_accept_consistent_state() {
+        assert_eq!(check(&sample_state()), vec![]);
+    }
+
+    #[test]
+    fn check_should_report_every_problem() {
+        let mut db_state = sample_state();
+        db_state.epics.get_mut(&1).unwrap().stories = vec![2, 2, 9];
+        db_state.epics.get_mut(&4).unwrap().stories = vec![2];
+        db_state.stories.insert(4, story());
+        db_state.last_item_id = 3;
+
+        assert_eq!(
+            check(&db_state),
+            vec![
+                Problem::DuplicateStory {
+                    epic_id: 1,
+                    story_id: 2
+                },
+                Problem::MissingStory {
+                    epic_id: 1,
+                    story_id: 9
+                },
+                Problem::SharedStory {
+                    story_id: 2,
+                    epic_ids: vec![1, 4]
+                },
+                Problem::OrphanStory { story_id: 3 },
+                Problem::OrphanStory { story_id: 4 },
+                Problem::IdClash { id: 4 },
+                Problem::StaleLastItemId {
+                    last_item_id: 3,
+                    max_id: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn repair_should_fix_repairable_problems() {
+        let mut db_state = sample_state();
+        db_state.epics.get_mut(&1).unwrap().stories = vec![2, 3, 2, 9];
+        db_state.epics.get_mut(&4).unwrap().stories = vec![3];
+        db_state.last_item_id = 1;
+
+        let fixed = repair(&mut db_state);
+
+        assert_eq!(fixed.len(), 4);
+        assert_eq!(db_state.epics[&1].stories, vec![2, 3]);
+        assert_eq!(db_state.epics[&4].stories, Vec::<u32>::new());
+        assert_eq!(db_state.last_item_id, 4);
+        assert_eq!(check(&db_state), vec![]);
+    }
+
+    #[test]
+    fn repair_should_leave_orphans_and_clashes() {
+        let mut db_state = sample_state();
+        db_state.stories.insert(4, story());
+
+        let fixed = repair(&mut db_state);
+
+        assert_eq!(fixed, vec![]);
+        assert_eq!(
+            check(&db_state),
+            vec![
+                Problem::OrphanStory { story_id: 4 },
+                Problem::IdClash { id: 4 }
+            ]
+        );
+    }
+
+    #[test]
+    fn repair_should_rebuild_label_index() {
+        let mut db_state = sample_state();
+        db_state.stories.get_mut(&2).unwrap().labels = vec!["backend".to_string()];
+        db_state.labels = BTreeMap::from([("frontend".to_string(), vec![3])]);
+
+        assert_eq!(check(&db_state), vec![Problem::StaleLabelIndex]);
+        assert_eq!(repair(&mut db_state), vec![Problem::StaleLabelIndex]);
+        assert_eq!(
+            db_state.labels,
+            BTreeMap::from([("backend".to_string(), vec![2])])
+        );
+    }
+
+    #[test]
+    fn repair_should_drop_comments_of_missing_stories() {
+        let mut db_state = sample_state();
+        let comment = Comment {
+            id: 1,
+            author: "jdoe".to_string(),
+            created_at: Utc::now(),
+            body: "hi".to_string(),
+            edited_at: None,
+        };
+        db_state.comments = HashMap::from([(2, vec![comment.clone()]), (9, vec![comment])]);
+
+        let stray = Problem::StrayComments { story_id: 9 };
+        assert_eq!(check(&db_state), vec![stray.clone()]);
+        assert_eq!(repair(&mut db_state), vec![stray]);
+        assert_eq!(db_state.comments.keys().collect::<Vec<_>>(), [&2]);
+    }
+
+    #[test]
+    fn repair_should_drop_dangling_links() {
+        let mut db_state = sample_state();
+        let link = |to| Link {
+            kind: LinkKind::Blocks,
+            to,
+        };
+        db_state.links = HashMap::from([(2, vec![link(3), link(9)]), (9, vec![link(2)])]);
+
+        let dangling = vec![
+            Problem::DanglingLink { from: 2, to: 9 },
+            Problem::DanglingLink { from: 9, to: 2 },
+        ];
+        assert_eq!(check(&db_state), dangling);
+        assert_eq!(repair(&mut db_state), dangling);
+        assert_eq!(db_state.links, HashMap::from([(2, vec![link(3)])]));
+    }
+
+    // epic 1 holds stories 2 and 3, epic 4 is empty
+    fn sample_state() -> DBState {
+        let mut epic = Epic::new("".to_string(), "".to_string());
+        epic.stories = vec![2, 3];
+
+        DBState {
+            schema_version: 1,
+            last_item_id: 4,
+            epics: HashMap::from([(1, epic), (4, Epic::new("".to_string(), "".to_string()))]),
+            stories: HashMap::from([(2, story()), (3, story())]),
+            users: HashMap::new(),
+            labels: BTreeMap::new(),
+            comments: HashMap::new(),
+            links: HashMap::new(),
+        }
+    }
+
+    fn story() -> Story {
+        Story::new("".to_string(), "".to_string())
+    }
+}
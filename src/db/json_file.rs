@@ -0,0 +1,771 @@
+use std::{
+    cell::RefCell,
+    fs::{self, File, OpenOptions, TryLockError},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use serde_json::Value;
+
+use super::{
+    backups::{self, Backup, BackupPolicy},
+    decode_history,
+    migrations::{self, CURRENT_SCHEMA_VERSION},
+    Database,
+};
+use crate::{
+    error::{JiraError, Result},
+    models::{DBState, History},
+};
+
+const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+enum LockMode {
+    Shared,
+    Exclusive,
+}
+
+pub struct JSONFileDatabase {
+    pub file_path: String,
+    lock_timeout: Duration,
+    // set while `with_write_lock` runs so nested reads and writes reuse its lock
+    write_lock: RefCell<Option<File>>,
+    // snapshots of the previous file are taken before every write when set
+    backup_policy: Option<BackupPolicy>,
+}
+
+// parses a database document of any supported schema version, migrating it in memory
+pub fn decode_db(db_content: &str) -> Result<DBState> {
+    decode_document(db_content).map(|(parsed, _)| parsed)
+}
+
+fn decode_document(db_content: &str) -> Result<(DBState, u32)> {
+    let mut document: Value = serde_json::from_str(db_content)?;
+    let version = migrations::schema_version(&document)?;
+
+    if version != CURRENT_SCHEMA_VERSION {
+        migrations::migrate(&mut document)?;
+    }
+
+    Ok((serde_json::from_value(document)?, version))
+}
+
+impl JSONFileDatabase {
+    pub fn new(file_path: String) -> Result<Self> {
+        let db = Self {
+            file_path,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            write_lock: RefCell::new(None),
+            backup_policy: None,
+        };
+        db.recover()?;
+        Ok(db)
+    }
+
+    pub fn with_backups(mut self, backup_policy: BackupPolicy) -> Self {
+        self.backup_policy = Some(backup_policy);
+        self
+    }
+
+    pub fn backups(&self) -> Result<Vec<Backup>> {
+        backups::list(&self.file_path)
+    }
+
+    // replaces the board with a snapshot. the file being replaced is backed up like on any other
+    // write, so a restore can itself be rolled back. undo must not lead into the board it replaced
+    pub fn restore(&self, backup: &Backup) -> Result<DBState> {
+        let db_state = backup.read()?;
+        self.with_write_lock(&mut || {
+            self.write_file(&db_state)?;
+            self.write_history_file(&History::default())
+        })?;
+        Ok(db_state)
+    }
+
+    // writes go here first and are renamed over `file_path` once they hit the disk
+    fn temp_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.tmp", self.file_path))
+    }
+
+    // the database file itself gets replaced on every write, so locks live on a sidecar file
+    fn lock_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.lock", self.file_path))
+    }
+
+    // the lock is released when the returned file is dropped
+    fn lock(&self, mode: LockMode) -> Result<File> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.lock_path())?;
+        let started = Instant::now();
+
+        loop {
+            let attempt = match mode {
+                LockMode::Shared => file.try_lock_shared(),
+                LockMode::Exclusive => file.try_lock(),
+            };
+
+            match attempt {
+                Ok(()) => return Ok(file),
+                Err(TryLockError::WouldBlock) if started.elapsed() < self.lock_timeout => {
+                    thread::sleep(LOCK_RETRY_INTERVAL)
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(JiraError::Locked(self.file_path.clone()))
+                }
+                Err(TryLockError::Error(error)) => return Err(error.into()),
+            }
+        }
+    }
+
+    // runs `f` with other writers shut out. nested reads and writes reuse the lock
+    fn with_write_lock(&self, f: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        if self.holds_write_lock() {
+            return f();
+        }
+
+        *self.write_lock.borrow_mut() = Some(self.lock(LockMode::Exclusive)?);
+        let result = f();
+        self.write_lock.borrow_mut().take();
+
+        result
+    }
+
+    fn holds_write_lock(&self) -> bool {
+        self.write_lock.borrow().is_some()
+    }
+
+    // a temp file left on startup means a write died before its rename.
+    // it only wins if the file it was meant to replace is gone or unreadable
+    fn recover(&self) -> Result<()> {
+        if !self.temp_path().exists() && !self.history_temp_path().exists() {
+            return Ok(());
+        }
+
+        self.with_write_lock(&mut || {
+            let temp_path = self.temp_path();
+            if temp_path.exists() {
+                let temp_is_valid = fs::read_to_string(&temp_path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<DBState>(&content).ok())
+                    .is_some();
+                let promote = self.read_file().is_err() && temp_is_valid;
+                promote_or_remove(&temp_path, Path::new(&self.file_path), promote)?;
+            }
+
+            let history_temp_path = self.history_temp_path();
+            if history_temp_path.exists() {
+                let is_valid = |path: &Path| {
+                    fs::read_to_string(path)
+                        .ok()
+                        .is_some_and(|content| decode_history(&content).is_ok())
+                };
+                let promote = !is_valid(&self.history_path()) && is_valid(&history_temp_path);
+                promote_or_remove(&history_temp_path, &self.history_path(), promote)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    // older documents are migrated in memory. they are only upgraded on disk, after a backup of
+    // the original, when the caller holds the write lock
+    fn read_file(&self) -> Result<(DBState, bool)> {
+        let db_content = match fs::read_to_string(&self.file_path) {
+            Ok(db_content) => db_content,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                return Err(JiraError::NoBoard(self.file_path.clone()))
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let (parsed, version) = decode_document(&db_content)?;
+
+        if version == CURRENT_SCHEMA_VERSION {
+            return Ok((parsed, false));
+        }
+
+        if !self.holds_write_lock() {
+            return Ok((parsed, true));
+        }
+
+        self.write_backup(version, &db_content)?;
+        self.write_file(&parsed)?;
+
+        Ok((parsed, false))
+    }
+
+    fn backup_path(&self, version: u32) -> PathBuf {
+        PathBuf::from(format!("{}.v{}.bak", self.file_path, version))
+    }
+
+    fn write_backup(&self, version: u32, db_content: &str) -> Result<()> {
+        let mut file = File::create(self.backup_path(version))?;
+        file.write_all(db_content.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    fn write_file(&self, db_state: &DBState) -> Result<()> {
+        if let Some(backup_policy) = &self.backup_policy {
+            backups::create(&self.file_path, backup_policy, Utc::now())?;
+        }
+
+        write_atomically(
+            Path::new(&self.file_path),
+            &self.temp_path(),
+            &serde_json::to_vec(db_state)?,
+        )
+    }
+
+    // undo history sits next to the database so the board file stays readable on its own
+    fn history_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.history", self.file_path))
+    }
+
+    fn read_history_file(&self) -> Result<History> {
+        match fs::read_to_string(self.history_path()) {
+            Ok(content) => decode_history(&content),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(History::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn history_temp_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.history.tmp", self.file_path))
+    }
+
+    fn write_history_file(&self, history: &History) -> Result<()> {
+        write_atomically(
+            &self.history_path(),
+            &self.history_temp_path(),
+            &serde_json::to_vec(history)?,
+        )
+    }
+}
+
+// writes `content` next to `path` first and renames it into place once it hits the disk, so a
+// crash leaves either the old file or the new one
+pub fn write_atomically(path: &Path, temp_path: &Path, content: &[u8]) -> Result<()> {
+    if let Err(error) = write_temp_file(temp_path, content) {
+        let _ = fs::remove_file(temp_path);
+        return Err(error);
+    }
+
+    fs::rename(temp_path, path)?;
+    sync_parent_dir(path)
+}
+
+fn write_temp_file(temp_path: &Path, content: &[u8]) -> Result<()> {
+    let mut file = File::create(temp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+// fsync the directory so the rename itself survives a power loss
+fn sync_parent_dir(path: &Path) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+fn promote_or_remove(temp_path: &Path, path: &Path, promote: bool) -> Result<()> {
+    if promote {
+        fs::rename(temp_path, path)?;
+    } else {
+        fs::remove_file(temp_path)?;
+    }
+
+    Ok(())
+}
+
+impl Database for JSONFileDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        if self.holds_write_lock() {
+            return self.read_file().map(|(parsed, _)| parsed);
+        }
+
+        let (parsed, needs_upgrade) = {
+            let _lock = self.lock(LockMode::Shared)?;
+            self.read_file()?
+        };
+
+        if needs_upgrade {
+            self.with_write_lock(&mut || self.read_file().map(|_| ()))?;
+        }
+
+        Ok(parsed)
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        if self.holds_write_lock() {
+            return self.write_file(db_state);
+        }
+
+        let _lock = self.lock(LockMode::Exclusive)?;
+        self.write_file(db_state)
+    }
+
+    fn read_history(&self) -> Result<History> {
+        if self.holds_write_lock() {
+            return self.read_history_file();
+        }
+
+        let _lock = self.lock(LockMode::Shared)?;
+        self.read_history_file()
+    }
+
+    fn write_history(&self, history: &History) -> Result<()> {
+        if self.holds_write_lock() {
+            return self.write_history_file(history);
+        }
+
+        let _lock = self.lock(LockMode::Exclusive)?;
+        self.write_history_file(history)
+    }
+
+    fn transaction(&self, f: &mut dyn FnMut(&mut DBState) -> Result<()>) -> Result<()> {
+        self.with_write_lock(&mut || {
+            let mut db_state = self.read_db()?;
+            f(&mut db_state)?;
+            self.write_db(&db_state)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::*;
+    use crate::{
+        db::{Backend, JiraDatabase},
+        models::{Epic, Priority, Status, Story},
+    };
+
+    #[test]
+    fn read_db_should_fail_with_invalid_path() {
+        let dir = tempfile::tempdir().unwrap();
+        // the directories exist, so the lock sidecar opens and it is the read itself that fails
+        fs::create_dir_all(dir.path().join("INVALID_DIR/INVALID_PATH")).unwrap();
+
+        let missing = dir.path().join("INVALID_DIR/MISSING").display().to_string();
+        let db = JSONFileDatabase::new(missing.clone()).unwrap();
+        assert!(matches!(db.read_db(), Err(JiraError::NoBoard(path)) if path == missing));
+
+        let directory = dir.path().join("INVALID_DIR/INVALID_PATH");
+        let db = JSONFileDatabase::new(directory.display().to_string()).unwrap();
+        assert!(matches!(db.read_db(), Err(JiraError::Io(_))));
+        assert!(db.lock_path().exists());
+    }
+
+    #[test]
+    fn read_db_should_fail_with_invalid_json() {
+        let file_content = r#"{ "last_item_id": 0 epics: {} stories {} }"#;
+        let result = read_json(file_content);
+        assert!(matches!(result, Err(JiraError::Corrupt(_))));
+    }
+
+    #[test]
+    fn read_db_should_parse_json_file() {
+        let file_content = r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#;
+        let result = read_json(file_content);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn write_db_should_work() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        let file_content = r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#;
+        fs::write(&db.file_path, file_content).unwrap();
+
+        let story = Story {
+            name: "story 1 name".to_string(),
+            description: "story 1 description".to_string(),
+            status: Status::new("open"),
+            priority: Priority::Medium,
+            assignee: None,
+            reporter: None,
+            labels: vec![],
+            due_date: None,
+            estimate: None,
+            subtasks: vec![],
+            created_at: None,
+            updated_at: None,
+        };
+        let epic = Epic {
+            name: "epic 1 name".to_string(),
+            description: "epic 1 description".to_string(),
+            status: Status::new("open"),
+            priority: Priority::Medium,
+            stories: vec![2],
+            assignee: None,
+            reporter: None,
+            labels: vec![],
+            due_date: None,
+            created_at: None,
+            updated_at: None,
+        };
+
+        let stories = HashMap::from([(2, story)]);
+        let epics = HashMap::from([(1, epic)]);
+
+        let state = DBState {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            last_item_id: 2,
+            epics,
+            stories,
+            users: HashMap::new(),
+            labels: BTreeMap::new(),
+            comments: HashMap::new(),
+            links: HashMap::new(),
+        };
+
+        let write_result = db.write_db(&state);
+        let read_result = db.read_db().unwrap();
+
+        assert!(write_result.is_ok());
+        assert_eq!(read_result, state);
+    }
+
+    #[test]
+    fn write_db_should_not_leave_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+
+        db.write_db(&empty_state()).unwrap();
+
+        assert!(Path::new(&db.file_path).exists());
+        assert!(!db.temp_path().exists());
+    }
+
+    #[test]
+    fn write_db_should_keep_original_when_interrupted() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        let mut state = empty_state();
+        db.write_db(&state).unwrap();
+
+        // a directory in the temp file's place makes the write fail before the rename
+        fs::create_dir(db.temp_path()).unwrap();
+        state.last_item_id = 1;
+
+        assert!(matches!(db.write_db(&state), Err(JiraError::Io(_))));
+        assert_eq!(db.read_db().unwrap(), empty_state());
+    }
+
+    #[test]
+    fn new_should_discard_stale_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        db.write_db(&empty_state()).unwrap();
+
+        // the process died halfway through writing the temp file
+        fs::write(db.temp_path(), r#"{ "last_item_id": 7, "epi"#).unwrap();
+
+        let db = JSONFileDatabase::new(db.file_path.clone()).unwrap();
+
+        assert!(!db.temp_path().exists());
+        assert_eq!(db.read_db().unwrap(), empty_state());
+    }
+
+    #[test]
+    fn new_should_keep_database_over_complete_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        db.write_db(&empty_state()).unwrap();
+
+        // the temp file was synced but the rename never happened
+        let mut unfinished = empty_state();
+        unfinished.last_item_id = 7;
+        fs::write(db.temp_path(), serde_json::to_vec(&unfinished).unwrap()).unwrap();
+
+        let db = JSONFileDatabase::new(db.file_path.clone()).unwrap();
+
+        assert!(!db.temp_path().exists());
+        assert_eq!(db.read_db().unwrap(), empty_state());
+    }
+
+    #[test]
+    fn new_should_restore_temp_file_when_database_is_unreadable() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+
+        fs::write(&db.file_path, r#"{ "last_item_id": 0, "#).unwrap();
+        fs::write(db.temp_path(), serde_json::to_vec(&empty_state()).unwrap()).unwrap();
+
+        let db = JSONFileDatabase::new(db.file_path.clone()).unwrap();
+
+        assert!(!db.temp_path().exists());
+        assert_eq!(db.read_db().unwrap(), empty_state());
+    }
+
+    #[test]
+    fn new_should_discard_stale_history_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        db.write_db(&empty_state()).unwrap();
+        db.write_history(&History::default()).unwrap();
+
+        // the process died halfway through writing the history
+        fs::write(db.history_temp_path(), r#"{ "undo": [ { "#).unwrap();
+
+        let db = JSONFileDatabase::new(db.file_path.clone()).unwrap();
+
+        assert!(!db.history_temp_path().exists());
+        assert_eq!(db.read_history().unwrap(), History::default());
+    }
+
+    #[test]
+    fn new_should_restore_history_temp_file_when_history_is_unreadable() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        db.write_db(&empty_state()).unwrap();
+
+        fs::write(db.history_path(), r#"{ "undo": "#).unwrap();
+        fs::write(
+            db.history_temp_path(),
+            serde_json::to_vec(&History::default()).unwrap(),
+        )
+        .unwrap();
+
+        let db = JSONFileDatabase::new(db.file_path.clone()).unwrap();
+
+        assert!(!db.history_temp_path().exists());
+        assert_eq!(db.read_history().unwrap(), History::default());
+    }
+
+    #[test]
+    fn read_db_should_fail_while_another_process_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = json_db_in(&dir);
+        db.write_db(&empty_state()).unwrap();
+        db.lock_timeout = Duration::from_millis(50);
+
+        let other_process = json_db_in(&dir);
+        let _lock = other_process.lock(LockMode::Exclusive).unwrap();
+
+        assert!(matches!(db.read_db(), Err(JiraError::Locked(_))));
+        assert!(matches!(
+            db.write_db(&empty_state()),
+            Err(JiraError::Locked(_))
+        ));
+    }
+
+    // set in the child process that holds the lock for the test below
+    const HOLD_LOCK_ENV: &str = "JIRA_TEST_HOLD_LOCK";
+
+    #[test]
+    fn read_db_should_fail_while_a_child_process_holds_the_lock() {
+        if let Ok(file_path) = std::env::var(HOLD_LOCK_ENV) {
+            let db = JSONFileDatabase::new(file_path.clone()).unwrap();
+            let _lock = db.lock(LockMode::Exclusive).unwrap();
+            fs::write(format!("{}.ready", file_path), "").unwrap();
+            // the parent closes stdin once it is done
+            std::io::Read::read_to_end(&mut std::io::stdin(), &mut vec![]).unwrap();
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = json_db_in(&dir);
+        db.write_db(&empty_state()).unwrap();
+        db.lock_timeout = Duration::from_millis(50);
+
+        let mut child = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "db::json_file::tests::read_db_should_fail_while_a_child_process_holds_the_lock",
+                "--exact",
+                "--nocapture",
+            ])
+            .env(HOLD_LOCK_ENV, &db.file_path)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let ready = PathBuf::from(format!("{}.ready", db.file_path));
+        let started = Instant::now();
+        while !ready.exists() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            thread::sleep(LOCK_RETRY_INTERVAL);
+        }
+
+        assert!(matches!(db.read_db(), Err(JiraError::Locked(_))));
+        assert!(matches!(
+            db.write_db(&empty_state()),
+            Err(JiraError::Locked(_))
+        ));
+
+        drop(child.stdin.take());
+        assert!(child.wait().unwrap().success());
+        assert_eq!(db.read_db().unwrap(), empty_state());
+    }
+
+    #[test]
+    fn read_db_should_share_lock_with_other_readers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut db = json_db_in(&dir);
+        db.write_db(&empty_state()).unwrap();
+        db.lock_timeout = Duration::from_millis(50);
+
+        let other_process = json_db_in(&dir);
+        let _lock = other_process.lock(LockMode::Shared).unwrap();
+
+        assert_eq!(db.read_db().unwrap(), empty_state());
+    }
+
+    #[test]
+    fn concurrent_writers_should_not_lose_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        json_db_in(&dir).write_db(&empty_state()).unwrap();
+
+        let writers = 4;
+        let epics_per_writer = 10;
+        let file_path = db_path_in(&dir);
+
+        let handles = (0..writers)
+            .map(|_| {
+                let file_path = file_path.clone();
+                thread::spawn(move || {
+                    let db = JiraDatabase::open(Backend::Json, file_path).unwrap();
+                    for _ in 0..epics_per_writer {
+                        db.create_epic(Epic::new("".to_string(), "".to_string()))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let db_state = json_db_in(&dir).read_db().unwrap();
+        assert_eq!(db_state.epics.len(), writers * epics_per_writer);
+        assert_eq!(db_state.last_item_id as usize, writers * epics_per_writer);
+    }
+
+    #[test]
+    fn read_db_should_upgrade_old_schema_after_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        let v0 = include_str!("../../tests/fixtures/schema_v0.json");
+        fs::write(&db.file_path, v0).unwrap();
+
+        let db_state = db.read_db().unwrap();
+
+        let on_disk: Value =
+            serde_json::from_str(&fs::read_to_string(&db.file_path).unwrap()).unwrap();
+        assert_eq!(db_state.schema_version, CURRENT_SCHEMA_VERSION);
+        assert_eq!(db_state.epics[&1].stories, vec![2, 3]);
+        assert_eq!(
+            migrations::schema_version(&on_disk).unwrap(),
+            CURRENT_SCHEMA_VERSION
+        );
+        assert_eq!(fs::read_to_string(db.backup_path(0)).unwrap(), v0);
+    }
+
+    #[test]
+    fn read_db_should_not_touch_current_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        let v7 = include_str!("../../tests/fixtures/schema_v7.json");
+        fs::write(&db.file_path, v7).unwrap();
+
+        db.read_db().unwrap();
+
+        assert_eq!(fs::read_to_string(&db.file_path).unwrap(), v7);
+        assert!(!db.backup_path(7).exists());
+    }
+
+    #[test]
+    fn read_db_should_fail_on_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        let newer = format!(
+            r#"{{ "schema_version": {}, "last_item_id": 0, "epics": {{}}, "stories": {{}} }}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        fs::write(&db.file_path, &newer).unwrap();
+
+        assert!(matches!(
+            db.read_db(),
+            Err(JiraError::UnsupportedSchemaVersion { .. })
+        ));
+        assert_eq!(fs::read_to_string(&db.file_path).unwrap(), newer);
+    }
+
+    #[test]
+    fn history_should_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        json_db_in(&dir).write_db(&empty_state()).unwrap();
+
+        let db = JiraDatabase::open(Backend::Json, db_path_in(&dir)).unwrap();
+        db.create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        drop(db);
+
+        let db = JiraDatabase::open(Backend::Json, db_path_in(&dir)).unwrap();
+        db.undo().unwrap();
+
+        assert_eq!(db.read_db().unwrap(), empty_state());
+    }
+
+    #[test]
+    fn read_history_should_drop_snapshots_from_other_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        fs::write(
+            db.history_path(),
+            r#"{ "undo": [{ "last_item_id": 0, "epics": {}, "stories": {} }], "redo": [] }"#,
+        )
+        .unwrap();
+
+        assert_eq!(db.read_history().unwrap(), History::default());
+    }
+
+    fn json_db_in(dir: &tempfile::TempDir) -> JSONFileDatabase {
+        JSONFileDatabase::new(db_path_in(dir)).unwrap()
+    }
+
+    fn db_path_in(dir: &tempfile::TempDir) -> String {
+        dir.path()
+            .join("db.json")
+            .to_str()
+            .expect("failed to convert tmpdir path to str")
+            .to_string()
+    }
+
+    fn empty_state() -> DBState {
+        DBState {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            last_item_id: 0,
+            epics: HashMap::new(),
+            stories: HashMap::new(),
+            users: HashMap::new(),
+            labels: BTreeMap::new(),
+            comments: HashMap::new(),
+            links: HashMap::new(),
+        }
+    }
+
+    fn read_json(file_contents: &str) -> Result<DBState> {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+
+        fs::write(&db.file_path, file_contents).unwrap();
+
+        db.read_db()
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    error::{JiraError, Result},
+    models::DBState,
+};
+
+// labels are compared case-insensitively and may not contain spaces or commas, so "Tech Debt" has
+// to be written as "tech-debt"
+pub fn normalize(label: &str) -> Result<String> {
+    let label = label.trim().to_lowercase();
+
+    if label.is_empty() || label.contains(|c: char| c.is_whitespace() || c == ',') {
+        return Err(JiraError::InvalidLabel(label));
+    }
+
+    Ok(label)
+}
+
+// the label index as it should be for the items in `db_state`
+pub fn index(db_state: &DBState) -> BTreeMap<String, Vec<u32>> {
+    let mut index = BTreeMap::new();
+
+    let epics = db_state.epics.iter().map(|(id, epic)| (id, &epic.labels));
+    let stories = db_state
+        .stories
+        .iter()
+        .map(|(id, story)| (id, &story.labels));
+    for (id, labels) in epics.chain(stories) {
+        for label in labels {
+            insert(&mut index, label, *id);
+        }
+    }
+
+    index
+}
+
+pub fn insert(index: &mut BTreeMap<String, Vec<u32>>, label: &str, id: u32) {
+    let ids = index.entry(label.to_string()).or_default();
+    if let Err(position) = ids.binary_search(&id) {
+        ids.insert(position, id);
+    }
+}
+
+// labels nothing carries any more are dropped from the index
+pub fn remove(index: &mut BTreeMap<String, Vec<u32>>, label: &str, id: u32) {
+    if let Some(ids) = index.get_mut(label) {
+        ids.retain(|item_id| *item_id != id);
+        if ids.is_empty() {
+            index.remove(label);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::models::{Epic, Story};
+
+    #[test]
+    fn normalize_should_lowercase_and_reject_separators() {
+        assert_eq!(normalize("  Backend ").unwrap(), "backend");
+        assert!(matches!(
+            normalize("tech debt"),
+            Err(JiraError::InvalidLabel(_))
+        ));
+        assert!(normalize("a,b").is_err());
+        assert!(normalize("   ").is_err());
+    }
+
+    #[test]
+    fn index_should_list_every_labelled_item_in_order() {
+        let mut epic = Epic::new("".to_string(), "".to_string());
+        epic.labels = vec!["backend".to_string()];
+        let mut story = Story::new("".to_string(), "".to_string());
+        story.labels = vec!["backend".to_string(), "tech-debt".to_string()];
+        let db_state = DBState {
+            schema_version: 0,
+            last_item_id: 2,
+            epics: HashMap::from([(2, epic)]),
+            stories: HashMap::from([(1, story)]),
+            users: HashMap::new(),
+            labels: BTreeMap::new(),
+            comments: HashMap::new(),
+            links: HashMap::new(),
+        };
+
+        let mut index = index(&db_state);
+
+        assert_eq!(
+            index,
+            BTreeMap::from([
+                ("backend".to_string(), vec![1, 2]),
+                ("tech-debt".to_string(), vec![1]),
+            ])
+        );
+
+        remove(&mut index, "tech-debt", 1);
+        insert(&mut index, "backend", 1);
+        assert_eq!(index, BTreeMap::from([("backend".to_string(), vec![1, 2])]));
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::models::{Link, LinkKind};
+
+// the links pointing at `id` as (item they go out of, kind) pairs, in ascending order
+pub fn inbound(links: &HashMap<u32, Vec<Link>>, id: u32) -> Vec<(u32, LinkKind)> {
+    links
+        .iter()
+        .flat_map(|(from, outbound)| outbound.iter().map(move |link| (*from, link)))
+        .filter(|(_, link)| link.to == id)
+        .map(|(from, link)| (from, link.kind))
+        .sorted()
+        .collect()
+}
+
+// whether `from` and `to` are linked that way already. "relates to" reads the same from both ends
+pub fn contains(links: &HashMap<u32, Vec<Link>>, from: u32, kind: LinkKind, to: u32) -> bool {
+    let has = |from: u32, to: u32| {
+        links
+            .get(&from)
+            .is_some_and(|outbound| outbound.contains(&Link { kind, to }))
+    };
+
+    has(from, to) || (kind == LinkKind::RelatesTo && has(to, from))
+}
+
+// the items `from` blocks, directly or through others, up to and including `to`
+pub fn blocking_path(links: &HashMap<u32, Vec<Link>>, from: u32, to: u32) -> Option<Vec<u32>> {
+    let mut stack = vec![vec![from]];
+    let mut seen = vec![from];
+
+    while let Some(path) = stack.pop() {
+        let last = path[path.len() - 1];
+        if last == to {
+            return Some(path);
+        }
+
+        for link in links.get(&last).into_iter().flatten() {
+            if link.kind == LinkKind::Blocks && !seen.contains(&link.to) {
+                seen.push(link.to);
+                stack.push([path.clone(), vec![link.to]].concat());
+            }
+        }
+    }
+
+    None
+}
+
+// returns whether the link was there. items left without links are dropped
+pub fn remove(links: &mut HashMap<u32, Vec<Link>>, from: u32, kind: LinkKind, to: u32) -> bool {
+    let Some(outbound) = links.get_mut(&from) else {
+        return false;
+    };
+    let before = outbound.len();
+    outbound.retain(|link| *link != Link { kind, to });
+    let removed = outbound.len() < before;
+
+    if outbound.is_empty() {
+        links.remove(&from);
+    }
+
+    removed
+}
+
+// drops every link going out of or pointing at `id`, for when the item is deleted
+pub fn remove_item(links: &mut HashMap<u32, Vec<Link>>, id: u32) {
+    links.remove(&id);
+    for outbound in links.values_mut() {
+        outbound.retain(|link| link.to != id);
+    }
+    links.retain(|_, outbound| !outbound.is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(to: u32) -> Link {
+        Link {
+            kind: LinkKind::Blocks,
+            to,
+        }
+    }
+
+    #[test]
+    fn blocking_path_should_follow_only_blocks_links() {
+        let links = HashMap::from([
+            (1, vec![blocks(2)]),
+            (2, vec![blocks(3)]),
+            (
+                3,
+                vec![Link {
+                    kind: LinkKind::RelatesTo,
+                    to: 4,
+                }],
+            ),
+        ]);
+
+        assert_eq!(blocking_path(&links, 1, 3), Some(vec![1, 2, 3]));
+        assert_eq!(blocking_path(&links, 1, 4), None);
+        assert_eq!(blocking_path(&links, 3, 1), None);
+    }
+
+    #[test]
+    fn remove_item_should_drop_links_on_both_ends() {
+        let mut links = HashMap::from([(1, vec![blocks(2), blocks(3)]), (2, vec![blocks(3)])]);
+
+        assert_eq!(
+            inbound(&links, 3),
+            [(1, LinkKind::Blocks), (2, LinkKind::Blocks)]
+        );
+
+        remove_item(&mut links, 2);
+
+        assert_eq!(links, HashMap::from([(1, vec![blocks(3)])]));
+    }
+
+    #[test]
+    fn contains_should_read_relates_to_both_ways() {
+        let links = HashMap::from([(
+            1,
+            vec![
+                blocks(2),
+                Link {
+                    kind: LinkKind::RelatesTo,
+                    to: 3,
+                },
+            ],
+        )]);
+
+        assert!(contains(&links, 1, LinkKind::Blocks, 2));
+        assert!(!contains(&links, 2, LinkKind::Blocks, 1));
+        assert!(contains(&links, 3, LinkKind::RelatesTo, 1));
+    }
+}
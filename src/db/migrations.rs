@@ -0,0 +1,199 @@
+use serde_json::Value;
+
+use crate::error::{JiraError, Result};
+
+pub const CURRENT_SCHEMA_VERSION: u32 = 7;
+
+type Migration = fn(&mut Value) -> Result<()>;
+
+// MIGRATIONS[n] upgrades a version n document to version n + 1
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] = [
+    v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7,
+];
+
+// v1 stored the variants of the old Status enum, v2 stores state ids of the default workflow
+pub const LEGACY_STATUSES: [(&str, &str); 4] = [
+    ("Open", "open"),
+    ("InProgress", "in_progress"),
+    ("Resolved", "resolved"),
+    ("Closed", "closed"),
+];
+
+// documents written before versioning existed have no schema_version at all
+pub fn schema_version(document: &Value) -> Result<u32> {
+    match document.get("schema_version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| JiraError::Corrupt(format!("invalid schema_version {version}"))),
+    }
+}
+
+// upgrades `document` one version at a time until it matches CURRENT_SCHEMA_VERSION
+pub fn migrate(document: &mut Value) -> Result<()> {
+    let version = schema_version(document)?;
+
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(JiraError::UnsupportedSchemaVersion {
+            found: version,
+            supported: CURRENT_SCHEMA_VERSION,
+        });
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(document)?;
+        set_schema_version(document, from as u32 + 1)?;
+    }
+
+    Ok(())
+}
+
+fn set_schema_version(document: &mut Value, version: u32) -> Result<()> {
+    document
+        .as_object_mut()
+        .ok_or_else(|| JiraError::Corrupt("database is not a json object".to_string()))?
+        .insert("schema_version".to_string(), Value::from(version));
+    Ok(())
+}
+
+// v1 only introduced the schema_version field itself
+fn v0_to_v1(_document: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+fn v1_to_v2(document: &mut Value) -> Result<()> {
+    for items in ["epics", "stories"] {
+        let items = match document.get_mut(items).and_then(Value::as_object_mut) {
+            Some(items) => items,
+            None => continue,
+        };
+
+        for status in items.values_mut().filter_map(|item| item.get_mut("status")) {
+            let legacy = LEGACY_STATUSES
+                .iter()
+                .find(|(legacy, _)| status.as_str() == Some(legacy));
+            if let Some((_, id)) = legacy {
+                *status = Value::from(*id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// v3 added the user registry
+fn v2_to_v3(document: &mut Value) -> Result<()> {
+    add_empty_object(document, "users")
+}
+
+// v4 gave every epic and story a priority
+fn v3_to_v4(document: &mut Value) -> Result<()> {
+    for items in ["epics", "stories"] {
+        let items = match document.get_mut(items).and_then(Value::as_object_mut) {
+            Some(items) => items,
+            None => continue,
+        };
+
+        for item in items.values_mut().filter_map(Value::as_object_mut) {
+            item.entry("priority")
+                .or_insert_with(|| Value::from("medium"));
+        }
+    }
+
+    Ok(())
+}
+
+// v5 added labels. nothing carried one before, so the label index starts out empty
+fn v4_to_v5(document: &mut Value) -> Result<()> {
+    add_empty_object(document, "labels")
+}
+
+fn v5_to_v6(document: &mut Value) -> Result<()> {
+    add_empty_object(document, "comments")
+}
+
+fn v6_to_v7(document: &mut Value) -> Result<()> {
+    add_empty_object(document, "links")
+}
+
+fn add_empty_object(document: &mut Value, key: &str) -> Result<()> {
+    document
+        .as_object_mut()
+        .ok_or_else(|| JiraError::Corrupt("database is not a json object".to_string()))?
+        .entry(key)
+        .or_insert_with(|| Value::Object(Default::default()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DBState;
+
+    const FIXTURES: [&str; CURRENT_SCHEMA_VERSION as usize + 1] = [
+        include_str!("../../tests/fixtures/schema_v0.json"),
+        include_str!("../../tests/fixtures/schema_v1.json"),
+        include_str!("../../tests/fixtures/schema_v2.json"),
+        include_str!("../../tests/fixtures/schema_v3.json"),
+        include_str!("../../tests/fixtures/schema_v4.json"),
+        include_str!("../../tests/fixtures/schema_v5.json"),
+        include_str!("../../tests/fixtures/schema_v6.json"),
+        include_str!("../../tests/fixtures/schema_v7.json"),
+    ];
+
+    #[test]
+    fn schema_version_should_default_to_zero() {
+        let document: Value = serde_json::from_str(FIXTURES[0]).unwrap();
+        assert_eq!(schema_version(&document).unwrap(), 0);
+    }
+
+    #[test]
+    fn schema_version_should_reject_invalid_values() {
+        let document = serde_json::json!({ "schema_version": "one" });
+        assert!(matches!(
+            schema_version(&document),
+            Err(JiraError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn migrate_should_upgrade_every_fixture_to_the_same_state() {
+        let mut latest: Value = serde_json::from_str(FIXTURES[FIXTURES.len() - 1]).unwrap();
+        migrate(&mut latest).unwrap();
+        let expected: DBState = serde_json::from_value(latest).unwrap();
+
+        for (version, fixture) in FIXTURES.iter().enumerate() {
+            let mut document: Value = serde_json::from_str(fixture).unwrap();
+            assert_eq!(schema_version(&document).unwrap(), version as u32);
+
+            migrate(&mut document).unwrap();
+
+            assert_eq!(schema_version(&document).unwrap(), CURRENT_SCHEMA_VERSION);
+            assert_eq!(
+                serde_json::from_value::<DBState>(document).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn migrate_should_map_legacy_statuses_to_default_workflow() {
+        let mut document: Value = serde_json::from_str(FIXTURES[1]).unwrap();
+
+        migrate(&mut document).unwrap();
+
+        assert_eq!(document["epics"]["1"]["status"], "in_progress");
+        assert_eq!(document["stories"]["2"]["status"], "open");
+        assert_eq!(document["stories"]["3"]["status"], "closed");
+    }
+
+    #[test]
+    fn migrate_should_reject_newer_versions() {
+        let mut document = serde_json::json!({ "schema_version": CURRENT_SCHEMA_VERSION + 1 });
+        assert!(matches!(
+            migrate(&mut document),
+            Err(JiraError::UnsupportedSchemaVersion { .. })
+        ));
+    }
+}
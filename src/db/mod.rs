@@ -0,0 +1,2242 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, mem,
+    path::Path,
+    rc::Rc,
+    str::FromStr,
+};
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    db::fsck::Problem,
+    error::{JiraError, Result},
+    estimate::EstimateScale,
+    models::{
+        Comment, DBState, Epic, History, Link, LinkKind, Priority, Status, Story, Subtask, User,
+    },
+    workflow::{Category, Workflow, Workflows},
+};
+use backups::BackupPolicy;
+pub use json_file::JSONFileDatabase;
+use migrations::CURRENT_SCHEMA_VERSION;
+use sqlite::SqliteDatabase;
+
+pub mod backups;
+pub mod fsck;
+mod json_file;
+mod labels;
+pub mod links;
+mod migrations;
+mod sqlite;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Json,
+    Sqlite,
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Backend::Json),
+            "sqlite" => Ok(Backend::Sqlite),
+            other => Err(format!(
+                "unknown storage backend \"{other}\", expected json or sqlite"
+            )),
+        }
+    }
+}
+
+pub const DEFAULT_HISTORY_DEPTH: usize = 20;
+
+pub struct JiraDatabase {
+    pub database: Box<dyn Database>,
+    // how many undo steps are kept, 0 turns recording off
+    history_depth: usize,
+    workflows: Workflows,
+    estimate_scale: EstimateScale,
+    clock: Rc<dyn Clock>,
+}
+
+impl JiraDatabase {
+    pub fn new(database: Box<dyn Database>) -> Self {
+        Self {
+            database,
+            history_depth: DEFAULT_HISTORY_DEPTH,
+            workflows: Workflows::default(),
+            estimate_scale: EstimateScale::default(),
+            clock: Rc::new(SystemClock),
+        }
+    }
+
+    pub fn with_history_depth(mut self, history_depth: usize) -> Self {
+        self.history_depth = history_depth;
+        self
+    }
+
+    pub fn with_workflows(mut self, workflows: Workflows) -> Self {
+        self.workflows = workflows;
+        self
+    }
+
+    pub fn workflows(&self) -> &Workflows {
+        &self.workflows
+    }
+
+    pub fn with_estimate_scale(mut self, estimate_scale: EstimateScale) -> Self {
+        self.estimate_scale = estimate_scale;
+        self
+    }
+
+    pub fn estimate_scale(&self) -> &EstimateScale {
+        &self.estimate_scale
+    }
+
+    // fn for testing
+    #[allow(dead_code)]
+    pub fn with_clock(mut self, clock: Rc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // the date due dates are measured against, in local time
+    pub fn today(&self) -> NaiveDate {
+        self.clock.now().with_timezone(&Local).date_naive()
+    }
+
+    pub fn open(backend: Backend, file_path: String) -> Result<Self> {
+        let database: Box<dyn Database> = match backend {
+            Backend::Json => Box::new(JSONFileDatabase::new(file_path)?),
+            Backend::Sqlite => {
+                let database = SqliteDatabase::open(&file_path)?;
+
+                // one-shot migration from the db.json sitting next to a brand new sqlite file
+                let json_path = Path::new(&file_path).with_extension("json");
+                if database.is_new()? && json_path.exists() {
+                    let json_path = json_path.to_string_lossy().to_string();
+                    database.import(&JSONFileDatabase::new(json_path)?)?;
+                }
+
+                Box::new(database)
+            }
+        };
+
+        Ok(Self::new(database))
+    }
+
+    // writes an empty board to `file_path`, creating its directory. an existing board is only
+    // replaced when `overwrite` is set, and is backed up first
+    pub fn create(backend: Backend, file_path: String, overwrite: bool) -> Result<()> {
+        let path = Path::new(&file_path);
+        if path.exists() {
+            if !overwrite {
+                return Err(JiraError::BoardExists(file_path));
+            }
+            backups::create(&file_path, &BackupPolicy::default(), Utc::now())?;
+            fs::remove_file(path)?;
+        }
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        let db = Self::open(backend, file_path)?;
+        db.database.write_db(&empty_state())?;
+        // undo must not lead back into the board that was replaced
+        db.database.write_history(&History::default())
+    }
+
+    pub fn read_db(&self) -> Result<DBState> {
+        self.database.read_db()
+    }
+
+    // runs `f` against the latest state. its changes are stored only if it returns Ok, and no
+    // other writer can get in between the read and the write
+    pub fn transaction<T>(&self, f: impl FnOnce(&mut DBState) -> Result<T>) -> Result<T> {
+        let mut f = Some(f);
+        let mut output = None;
+
+        self.database.transaction(&mut |db_state| {
+            if let Some(f) = f.take() {
+                let before = db_state.clone();
+                output = Some(f(db_state)?);
+                stamp_changes(&before, db_state, self.clock.now());
+                self.record_change(before, db_state)?;
+            }
+            Ok(())
+        })?;
+
+        output.ok_or_else(|| JiraError::Conflict("transaction was never run".to_string()))
+    }
+
+    pub fn undo(&self) -> Result<()> {
+        self.database.transaction(&mut |db_state| {
+            let mut history = self.database.read_history()?;
+            let previous = history.undo.pop().ok_or(JiraError::NothingToUndo)?;
+
+            history.redo.push(mem::replace(db_state, previous));
+            self.write_history(history)
+        })
+    }
+
+    pub fn redo(&self) -> Result<()> {
+        self.database.transaction(&mut |db_state| {
+            let mut history = self.database.read_history()?;
+            let next = history.redo.pop().ok_or(JiraError::NothingToRedo)?;
+
+            history.undo.push(mem::replace(db_state, next));
+            self.write_history(history)
+        })
+    }
+
+    // runs inside the backend transaction so the history is written together with the change
+    fn record_change(&self, before: DBState, after: &DBState) -> Result<()> {
+        if self.history_depth == 0 || &before == after {
+            return Ok(());
+        }
+
+        let mut history = self.database.read_history()?;
+
+        history.undo.push(before);
+        if history.undo.len() > self.history_depth {
+            let overflow = history.undo.len() - self.history_depth;
+            history.undo.drain(..overflow);
+        }
+        history.redo.clear();
+
+        self.write_history(history)
+    }
+
+    fn write_history(&self, mut history: History) -> Result<()> {
+        history.schema_version = CURRENT_SCHEMA_VERSION;
+        self.database.write_history(&history)
+    }
+
+    // new epics and stories always start in the initial state of their workflow
+    pub fn create_epic(&self, mut epic: Epic) -> Result<u32> {
+        epic.status = self.workflows.epic.initial.clone();
+
+        self.transaction(|db_state| {
+            check_users(db_state, [&epic.assignee, &epic.reporter])?;
+            let new_id = next_item_id(db_state)?;
+
+            db_state.last_item_id = new_id;
+            for label in &epic.labels {
+                labels::insert(&mut db_state.labels, label, new_id);
+            }
+            db_state.epics.insert(new_id, epic);
+
+            Ok(new_id)
+        })
+    }
+
+    pub fn create_story(&self, mut story: Story, epic_id: u32) -> Result<u32> {
+        story.status = self.workflows.story.initial.clone();
+
+        self.transaction(|db_state| {
+            check_users(db_state, [&story.assignee, &story.reporter])?;
+            let new_id = next_item_id(db_state)?;
+
+            db_state.last_item_id = new_id;
+            for label in &story.labels {
+                labels::insert(&mut db_state.labels, label, new_id);
+            }
+            db_state.stories.insert(new_id, story);
+            db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?
+                .stories
+                .push(new_id);
+
+            Ok(new_id)
+        })
+    }
+
+    pub fn delete_epic(&self, epic_id: u32) -> Result<()> {
+        self.transaction(|db_state| {
+            db_state
+                .epics
+                .get(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?
+                .stories
+                .iter()
+                .for_each(|story_id| {
+                    db_state.stories.remove(story_id);
+                    db_state.comments.remove(story_id);
+                    links::remove_item(&mut db_state.links, *story_id);
+                });
+
+            db_state.epics.remove(&epic_id);
+            links::remove_item(&mut db_state.links, epic_id);
+            db_state.labels = labels::index(db_state);
+
+            Ok(())
+        })
+    }
+
+    pub fn delete_story(&self, epic_id: u32, story_id: u32) -> Result<()> {
+        self.transaction(|db_state| {
+            let epic = db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?;
+            let story_index = epic
+                .stories
+                .iter()
+                .position(|id| id == &story_id)
+                .ok_or(JiraError::StoryNotInEpic { epic_id, story_id })?;
+
+            epic.stories.remove(story_index);
+            db_state.stories.remove(&story_id);
+            db_state.comments.remove(&story_id);
+            links::remove_item(&mut db_state.links, story_id);
+            db_state.labels = labels::index(db_state);
+
+            Ok(())
+        })
+    }
+
+    pub fn update_epic(
+        &self,
+        epic_id: u32,
+        name: String,
+        description: String,
+        priority: Priority,
+        due_date: Option<NaiveDate>,
+    ) -> Result<()> {
+        self.transaction(|db_state| {
+            let epic = db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?;
+
+            epic.name = name;
+            epic.description = description;
+            epic.priority = priority;
+            epic.due_date = due_date;
+
+            Ok(())
+        })
+    }
+
+    pub fn update_story(
+        &self,
+        story_id: u32,
+        name: String,
+        description: String,
+        priority: Priority,
+        due_date: Option<NaiveDate>,
+    ) -> Result<()> {
+        self.transaction(|db_state| {
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or(JiraError::StoryNotFound(story_id))?;
+
+            story.name = name;
+            story.description = description;
+            story.priority = priority;
+            story.due_date = due_date;
+
+            Ok(())
+        })
+    }
+
+    pub fn move_story(&self, story_id: u32, from_epic_id: u32, to_epic_id: u32) -> Result<()> {
+        self.transaction(|db_state| {
+            if !db_state.stories.contains_key(&story_id) {
+                return Err(JiraError::StoryNotFound(story_id));
+            }
+            if !db_state.epics.contains_key(&to_epic_id) {
+                return Err(JiraError::EpicNotFound(to_epic_id));
+            }
+
+            let from_epic = db_state
+                .epics
+                .get_mut(&from_epic_id)
+                .ok_or(JiraError::EpicNotFound(from_epic_id))?;
+            let story_index = from_epic
+                .stories
+                .iter()
+                .position(|id| id == &story_id)
+                .ok_or(JiraError::StoryNotInEpic {
+                    epic_id: from_epic_id,
+                    story_id,
+                })?;
+
+            if from_epic_id == to_epic_id {
+                return Ok(());
+            }
+
+            from_epic.stories.remove(story_index);
+            if let Some(to_epic) = db_state.epics.get_mut(&to_epic_id) {
+                to_epic.stories.push(story_id);
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn update_epic_status(&self, epic_id: u32, status: Status) -> Result<()> {
+        self.transaction(|db_state| {
+            let epic = db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?;
+
+            check_transition(&self.workflows.epic, &epic.status, &status)?;
+
+            let finishing = self.workflows.epic.category_of(&status) == Some(Category::Done);
+            if finishing && self.workflows.roll_up.block_done {
+                let open = epic
+                    .stories
+                    .iter()
+                    .filter_map(|story_id| db_state.stories.get(story_id))
+                    .filter(|story| {
+                        self.workflows.story.category_of(&story.status) != Some(Category::Done)
+                    })
+                    .count();
+                if open > 0 {
+                    return Err(JiraError::UnfinishedStories { epic_id, open });
+                }
+            }
+
+            epic.status = status;
+
+            Ok(())
+        })
+    }
+
+    pub fn update_story_status(&self, story_id: u32, status: Status) -> Result<()> {
+        self.transaction(|db_state| {
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or(JiraError::StoryNotFound(story_id))?;
+
+            check_transition(&self.workflows.story, &story.status, &status)?;
+
+            let starting = self.workflows.story.category_of(&status) != Some(Category::Todo);
+            story.status = status;
+
+            if starting && self.workflows.roll_up.auto_start {
+                let epic = db_state
+                    .epics
+                    .values_mut()
+                    .find(|epic| epic.stories.contains(&story_id));
+                if let Some(epic) = epic {
+                    start_epic(&self.workflows.epic, epic);
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    pub fn add_user(&self, id: String, name: String) -> Result<()> {
+        self.transaction(|db_state| {
+            if db_state.users.contains_key(&id) {
+                return Err(JiraError::UserExists(id));
+            }
+
+            db_state.users.insert(id, User { name });
+
+            Ok(())
+        })
+    }
+
+    pub fn assign_epic(
+        &self,
+        epic_id: u32,
+        assignee: Option<String>,
+        reporter: Option<String>,
+    ) -> Result<()> {
+        self.transaction(|db_state| {
+            check_users(db_state, [&assignee, &reporter])?;
+            let epic = db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?;
+
+            epic.assignee = assignee;
+            epic.reporter = reporter;
+
+            Ok(())
+        })
+    }
+
+    pub fn assign_story(
+        &self,
+        story_id: u32,
+        assignee: Option<String>,
+        reporter: Option<String>,
+    ) -> Result<()> {
+        self.transaction(|db_state| {
+            check_users(db_state, [&assignee, &reporter])?;
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or(JiraError::StoryNotFound(story_id))?;
+
+            story.assignee = assignee;
+            story.reporter = reporter;
+
+            Ok(())
+        })
+    }
+
+    // every label in use with the number of epics and stories carrying it, read off the index
+    pub fn label_counts(&self) -> Result<Vec<(String, usize)>> {
+        Ok(self
+            .read_db()?
+            .labels
+            .into_iter()
+            .map(|(label, ids)| (label, ids.len()))
+            .collect())
+    }
+
+    // links two epics or stories, returns whether they were not linked that way yet
+    pub fn link(&self, from: u32, kind: LinkKind, to: u32) -> Result<bool> {
+        if from == to {
+            return Err(JiraError::SelfLink(from));
+        }
+
+        self.transaction(|db_state| {
+            if let Some(id) = [from, to].into_iter().find(|id| !is_item(db_state, *id)) {
+                return Err(JiraError::ItemNotFound(id));
+            }
+            if links::contains(&db_state.links, from, kind, to) {
+                return Ok(false);
+            }
+            if kind == LinkKind::Blocks {
+                if let Some(path) = links::blocking_path(&db_state.links, to, from) {
+                    let cycle = [vec![from], path].concat();
+                    return Err(JiraError::BlockCycle(cycle.iter().join(" -> ")));
+                }
+            }
+
+            db_state
+                .links
+                .entry(from)
+                .or_default()
+                .push(Link { kind, to });
+
+            Ok(true)
+        })
+    }
+
+    // returns whether the items were linked that way
+    pub fn unlink(&self, from: u32, kind: LinkKind, to: u32) -> Result<bool> {
+        self.transaction(|db_state| {
+            let removed = links::remove(&mut db_state.links, from, kind, to)
+                || (kind == LinkKind::RelatesTo
+                    && links::remove(&mut db_state.links, to, kind, from));
+
+            Ok(removed)
+        })
+    }
+
+    // the epics and stories blocking `id` that are not in a `done` state yet
+    pub fn open_blockers(&self, id: u32) -> Result<Vec<u32>> {
+        let db_state = self.read_db()?;
+
+        Ok(links::inbound(&db_state.links, id)
+            .into_iter()
+            .filter(|(_, kind)| *kind == LinkKind::Blocks)
+            .map(|(from, _)| from)
+            .filter(|from| {
+                let category = match (db_state.epics.get(from), db_state.stories.get(from)) {
+                    (Some(epic), _) => self.workflows.epic.category_of(&epic.status),
+                    (None, Some(story)) => self.workflows.story.category_of(&story.status),
+                    (None, None) => return false,
+                };
+                category != Some(Category::Done)
+            })
+            .collect())
+    }
+
+    // `None` removes the estimate
+    pub fn estimate_story(&self, story_id: u32, estimate: Option<u32>) -> Result<()> {
+        if let Some(estimate) = estimate {
+            if !self.estimate_scale.allows(estimate) {
+                return Err(JiraError::InvalidEstimate {
+                    estimate,
+                    scale: self.estimate_scale.to_string(),
+                });
+            }
+        }
+
+        self.transaction(|db_state| {
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or(JiraError::StoryNotFound(story_id))?;
+
+            story.estimate = estimate;
+
+            Ok(())
+        })
+    }
+
+    pub fn add_subtask(&self, story_id: u32, name: &str) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(JiraError::EmptySubtask);
+        }
+
+        self.transaction(|db_state| {
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or(JiraError::StoryNotFound(story_id))?;
+
+            story.subtasks.push(Subtask::new(name.to_string()));
+
+            Ok(())
+        })
+    }
+
+    // returns whether the subtask is done now
+    pub fn toggle_subtask(&self, story_id: u32, index: usize) -> Result<bool> {
+        self.transaction(|db_state| {
+            let subtask = subtasks_of(db_state, story_id)?
+                .get_mut(index)
+                .ok_or(JiraError::SubtaskNotFound { story_id, index })?;
+
+            subtask.done = !subtask.done;
+
+            Ok(subtask.done)
+        })
+    }
+
+    pub fn remove_subtask(&self, story_id: u32, index: usize) -> Result<()> {
+        self.transaction(|db_state| {
+            let subtasks = subtasks_of(db_state, story_id)?;
+            if index >= subtasks.len() {
+                return Err(JiraError::SubtaskNotFound { story_id, index });
+            }
+
+            subtasks.remove(index);
+
+            Ok(())
+        })
+    }
+
+    // takes the subtask at `from` out and puts it back in at `to`
+    pub fn move_subtask(&self, story_id: u32, from: usize, to: usize) -> Result<()> {
+        self.transaction(|db_state| {
+            let subtasks = subtasks_of(db_state, story_id)?;
+            if let Some(index) = [from, to]
+                .into_iter()
+                .find(|index| *index >= subtasks.len())
+            {
+                return Err(JiraError::SubtaskNotFound { story_id, index });
+            }
+
+            let subtask = subtasks.remove(from);
+            subtasks.insert(to, subtask);
+
+            Ok(())
+        })
+    }
+
+    // returns the id of the new comment
+    pub fn add_comment(&self, story_id: u32, author: String, body: String) -> Result<u32> {
+        let body = comment_body(body)?;
+        let now = self.clock.now();
+
+        self.transaction(|db_state| {
+            if !db_state.stories.contains_key(&story_id) {
+                return Err(JiraError::StoryNotFound(story_id));
+            }
+            check_users(db_state, [&Some(author.clone())])?;
+
+            let comments = db_state.comments.entry(story_id).or_default();
+            let id = comments.iter().map(|comment| comment.id).max().unwrap_or(0) + 1;
+            comments.push(Comment {
+                id,
+                author,
+                created_at: now,
+                body,
+                edited_at: None,
+            });
+
+            Ok(id)
+        })
+    }
+
+    // only the author may change a comment
+    pub fn edit_comment(
+        &self,
+        story_id: u32,
+        comment_id: u32,
+        author: &str,
+        body: String,
+    ) -> Result<()> {
+        let body = comment_body(body)?;
+        let now = self.clock.now();
+
+        self.transaction(|db_state| {
+            let comment = own_comment(db_state, story_id, comment_id, author)?;
+
+            comment.body = body;
+            comment.edited_at = Some(now);
+
+            Ok(())
+        })
+    }
+
+    pub fn delete_comment(&self, story_id: u32, comment_id: u32, author: &str) -> Result<()> {
+        self.transaction(|db_state| {
+            own_comment(db_state, story_id, comment_id, author)?;
+
+            let comments = db_state.comments.entry(story_id).or_default();
+            comments.retain(|comment| comment.id != comment_id);
+            if comments.is_empty() {
+                db_state.comments.remove(&story_id);
+            }
+
+            Ok(())
+        })
+    }
+
+    // returns whether the epic did not carry the label yet
+    pub fn add_epic_label(&self, epic_id: u32, label: &str) -> Result<bool> {
+        let label = labels::normalize(label)?;
+
+        self.transaction(|db_state| {
+            let epic = db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?;
+
+            Ok(add_label(
+                &mut epic.labels,
+                &mut db_state.labels,
+                epic_id,
+                label,
+            ))
+        })
+    }
+
+    // returns whether the epic carried the label
+    pub fn remove_epic_label(&self, epic_id: u32, label: &str) -> Result<bool> {
+        self.transaction(|db_state| {
+            let epic = db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?;
+
+            Ok(remove_label(
+                &mut epic.labels,
+                &mut db_state.labels,
+                epic_id,
+                label,
+            ))
+        })
+    }
+
+    pub fn add_story_label(&self, story_id: u32, label: &str) -> Result<bool> {
+        let label = labels::normalize(label)?;
+
+        self.transaction(|db_state| {
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or(JiraError::StoryNotFound(story_id))?;
+
+            Ok(add_label(
+                &mut story.labels,
+                &mut db_state.labels,
+                story_id,
+                label,
+            ))
+        })
+    }
+
+    pub fn remove_story_label(&self, story_id: u32, label: &str) -> Result<bool> {
+        self.transaction(|db_state| {
+            let story = db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or(JiraError::StoryNotFound(story_id))?;
+
+            Ok(remove_label(
+                &mut story.labels,
+                &mut db_state.labels,
+                story_id,
+                label,
+            ))
+        })
+    }
+
+    pub fn check(&self) -> Result<Vec<Problem>> {
+        Ok(fsck::check(&self.read_db()?))
+    }
+
+    // fixes what can be fixed safely and returns those problems. the repair can be undone
+    pub fn repair(&self) -> Result<Vec<Problem>> {
+        self.transaction(|db_state| Ok(fsck::repair(db_state)))
+    }
+}
+
+// setting the current state again is a harmless no-op
+fn check_transition(workflow: &Workflow, from: &Status, to: &Status) -> Result<()> {
+    if from == to || workflow.can_transition(from, to) {
+        return Ok(());
+    }
+
+    Err(JiraError::IllegalTransition {
+        from: workflow.name_of(from),
+        to: workflow.name_of(to),
+    })
+}
+
+// epics and stories share one id space
+fn is_item(db_state: &DBState, id: u32) -> bool {
+    db_state.epics.contains_key(&id) || db_state.stories.contains_key(&id)
+}
+
+fn subtasks_of(db_state: &mut DBState, story_id: u32) -> Result<&mut Vec<Subtask>> {
+    db_state
+        .stories
+        .get_mut(&story_id)
+        .map(|story| &mut story.subtasks)
+        .ok_or(JiraError::StoryNotFound(story_id))
+}
+
+fn comment_body(body: String) -> Result<String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(JiraError::EmptyComment);
+    }
+
+    Ok(body.to_string())
+}
+
+fn own_comment<'a>(
+    db_state: &'a mut DBState,
+    story_id: u32,
+    comment_id: u32,
+    author: &str,
+) -> Result<&'a mut Comment> {
+    let comment = db_state
+        .comments
+        .get_mut(&story_id)
+        .and_then(|comments| comments.iter_mut().find(|comment| comment.id == comment_id))
+        .ok_or(JiraError::CommentNotFound {
+            story_id,
+            comment_id,
+        })?;
+
+    if comment.author != author {
+        return Err(JiraError::NotCommentAuthor(comment_id));
+    }
+
+    Ok(comment)
+}
+
+// new items get both timestamps, changed ones a new `updated_at`
+fn stamp_changes(before: &DBState, after: &mut DBState, now: DateTime<Utc>) {
+    for (id, epic) in after.epics.iter_mut() {
+        match before.epics.get(id) {
+            None => {
+                epic.created_at = Some(now);
+                epic.updated_at = Some(now);
+            }
+            Some(old) if old != epic => epic.updated_at = Some(now),
+            Some(_) => {}
+        }
+    }
+
+    for (id, story) in after.stories.iter_mut() {
+        match before.stories.get(id) {
+            None => {
+                story.created_at = Some(now);
+                story.updated_at = Some(now);
+            }
+            Some(old) if old != story => story.updated_at = Some(now),
+            Some(_) => {}
+        }
+    }
+}
+
+fn add_label(
+    item_labels: &mut Vec<String>,
+    index: &mut BTreeMap<String, Vec<u32>>,
+    id: u32,
+    label: String,
+) -> bool {
+    if item_labels.contains(&label) {
+        return false;
+    }
+
+    labels::insert(index, &label, id);
+    item_labels.push(label);
+    true
+}
+
+fn remove_label(
+    item_labels: &mut Vec<String>,
+    index: &mut BTreeMap<String, Vec<u32>>,
+    id: u32,
+    label: &str,
+) -> bool {
+    let before = item_labels.len();
+    item_labels.retain(|item_label| item_label != label);
+    labels::remove(index, label, id);
+
+    item_labels.len() != before
+}
+
+// people can only be put on items once they are in the registry
+fn check_users<const N: usize>(db_state: &DBState, ids: [&Option<String>; N]) -> Result<()> {
+    match ids
+        .into_iter()
+        .flatten()
+        .find(|id| !db_state.users.contains_key(*id))
+    {
+        Some(id) => Err(JiraError::UserNotFound(id.clone())),
+        None => Ok(()),
+    }
+}
+
+// moves an epic that has not started yet to the first in progress state it may move to
+fn start_epic(workflow: &Workflow, epic: &mut Epic) {
+    if workflow.category_of(&epic.status) != Some(Category::Todo) {
+        return;
+    }
+
+    let target = workflow
+        .targets(&epic.status)
+        .into_iter()
+        .find(|state| state.category == Category::InProgress);
+    if let Some(target) = target {
+        epic.status = target.id.clone();
+    }
+}
+
+fn empty_state() -> DBState {
+    DBState {
+        schema_version: CURRENT_SCHEMA_VERSION,
+        last_item_id: 0,
+        epics: HashMap::new(),
+        stories: HashMap::new(),
+        users: HashMap::new(),
+        labels: BTreeMap::new(),
+        comments: HashMap::new(),
+        links: HashMap::new(),
+    }
+}
+
+// a fresh id that nothing in the database is using yet
+fn next_item_id(db_state: &DBState) -> Result<u32> {
+    let new_id = db_state.last_item_id + 1;
+
+    if db_state.epics.contains_key(&new_id) || db_state.stories.contains_key(&new_id) {
+        return Err(JiraError::Conflict(format!(
+            "item id {new_id} is already in use"
+        )));
+    }
+
+    Ok(new_id)
+}
+
+// undo snapshots from an older schema can't be restored onto the current one, so they are dropped
+fn decode_history(content: &str) -> Result<History> {
+    let document: Value = serde_json::from_str(content)?;
+
+    if migrations::schema_version(&document)? != CURRENT_SCHEMA_VERSION {
+        return Ok(History::default());
+    }
+
+    Ok(serde_json::from_value(document)?)
+}
+
+pub trait Database {
+    fn read_db(&self) -> Result<DBState>;
+    fn write_db(&self, db_state: &DBState) -> Result<()>;
+    fn read_history(&self) -> Result<History>;
+    fn write_history(&self, history: &History) -> Result<()>;
+
+    // read -> modify -> write as one unit: nothing is stored when `f` fails, and backends
+    // that can be shared must keep other writers out until it is done
+    fn transaction(&self, f: &mut dyn FnMut(&mut DBState) -> Result<()>) -> Result<()> {
+        let mut db_state = self.read_db()?;
+        f(&mut db_state)?;
+        self.write_db(&db_state)
+    }
+}
+
+pub mod test_utils {
+    use std::{cell::RefCell, collections::HashMap};
+
+    use super::*;
+
+    pub struct MockDB {
+        last_written_state: RefCell<DBState>,
+        history: RefCell<History>,
+    }
+
+    // bc test util
+    #[allow(dead_code)]
+    impl MockDB {
+        pub fn new() -> Self {
+            Self {
+                last_written_state: RefCell::new(DBState {
+                    schema_version: CURRENT_SCHEMA_VERSION,
+                    last_item_id: 0,
+                    epics: HashMap::new(),
+                    stories: HashMap::new(),
+                    users: HashMap::new(),
+                    labels: BTreeMap::new(),
+                    comments: HashMap::new(),
+                    links: HashMap::new(),
+                }),
+                history: RefCell::new(History::default()),
+            }
+        }
+    }
+
+    impl Database for MockDB {
+        fn read_db(&self) -> Result<DBState> {
+            let state = self.last_written_state.borrow().clone();
+            Ok(state)
+        }
+
+        fn write_db(&self, db_state: &DBState) -> Result<()> {
+            let latest_state = &self.last_written_state;
+            *latest_state.borrow_mut() = db_state.clone();
+            Ok(())
+        }
+
+        fn read_history(&self) -> Result<History> {
+            Ok(self.history.borrow().clone())
+        }
+
+        fn write_history(&self, history: &History) -> Result<()> {
+            *self.history.borrow_mut() = history.clone();
+            Ok(())
+        }
+    }
+
+    pub struct FailingDB {
+        state: DBState,
+    }
+
+    // bc test util
+    #[allow(dead_code)]
+    impl FailingDB {
+        pub fn new(state: DBState) -> Self {
+            Self { state }
+        }
+    }
+
+    impl Database for FailingDB {
+        fn read_db(&self) -> Result<DBState> {
+            Ok(self.state.clone())
+        }
+
+        fn write_db(&self, _db_state: &DBState) -> Result<()> {
+            Err(JiraError::Io(std::io::Error::other("disk is full")))
+        }
+
+        fn read_history(&self) -> Result<History> {
+            Ok(History::default())
+        }
+
+        fn write_history(&self, _history: &History) -> Result<()> {
+            Err(JiraError::Io(std::io::Error::other("disk is full")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::test_utils::{FailingDB, MockDB};
+    use super::*;
+    use crate::clock::FixedClock;
+
+    // every JiraDatabase test runs once per storage backend
+    macro_rules! backend_tests {
+        ($($test:ident),* $(,)?) => {
+            mod mock {
+                use super::*;
+
+                $(
+                    #[test]
+                    fn $test() {
+                        super::$test(
+                            JiraDatabase::new(Box::new(MockDB::new()))
+                                .with_clock(Rc::new(FixedClock::default())),
+                        );
+                    }
+                )*
+            }
+
+            mod json_file {
+                use super::*;
+
+                $(
+                    #[test]
+                    fn $test() {
+                        let dir = tempfile::tempdir().unwrap();
+                        super::$test(json_db_in(&dir));
+                    }
+                )*
+            }
+
+            mod sqlite {
+                use super::*;
+
+                $(
+                    #[test]
+                    fn $test() {
+                        let dir = tempfile::tempdir().unwrap();
+                        super::$test(sqlite_db_in(&dir));
+                    }
+                )*
+            }
+        };
+    }
+
+    backend_tests!(
+        create_epic_should_work,
+        create_epic_should_error_if_next_id_is_taken,
+        create_story_should_error_if_invalid_epic_id,
+        create_story_should_work,
+        delete_epic_should_error_if_invalid_epic_id,
+        delete_epic_should_work,
+        delete_story_should_error_if_invalid_epic_id,
+        delete_story_should_error_if_story_not_found_in_epic,
+        delete_story_should_work,
+        update_epic_status_should_error_if_invalid_epic_id,
+        update_epic_status_should_work,
+        update_story_status_should_error_if_invalid_story_id,
+        update_story_status_should_work,
+        create_should_start_in_initial_state,
+        update_story_status_should_reject_illegal_transition,
+        update_epic_status_should_block_done_with_unfinished_stories,
+        update_story_status_should_start_epic,
+        transaction_should_commit_every_change,
+        transaction_should_roll_back_on_error,
+        undo_should_restore_previous_state,
+        undo_should_error_when_history_is_empty,
+        redo_should_reapply_undone_change,
+        redo_should_be_cleared_by_new_change,
+        history_should_be_limited_to_depth,
+        history_should_not_record_failed_transactions,
+        repair_should_fix_dangling_story,
+        update_epic_should_work,
+        update_epic_should_error_if_invalid_epic_id,
+        update_story_should_work,
+        update_story_should_error_if_invalid_story_id,
+        move_story_should_work,
+        move_story_should_error_if_target_epic_missing,
+        move_story_should_error_if_story_not_in_epic,
+        add_user_should_refuse_duplicate_id,
+        assign_story_should_work,
+        assign_should_require_known_users,
+        labels_should_keep_index_in_sync,
+        add_label_should_reject_invalid_label,
+        estimate_story_should_only_accept_points_on_scale,
+        subtasks_should_keep_their_order,
+        link_should_refuse_blocking_cycles,
+        open_blockers_should_skip_done_items,
+        delete_story_should_remove_its_links,
+        comments_should_only_be_changed_by_their_author,
+        delete_story_should_remove_its_comments,
+    );
+
+    #[test]
+    fn transaction_should_stamp_only_changed_items() {
+        let clock = Rc::new(FixedClock::default());
+        let db = JiraDatabase::new(Box::new(MockDB::new())).with_clock(clock.clone());
+        let created = clock.now();
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let other_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        clock.advance(Duration::hours(1));
+        db.update_story_status(story_id, Status::new("in_progress"))
+            .unwrap();
+
+        let db_state = db.read_db().unwrap();
+        let story = &db_state.stories[&story_id];
+        assert_eq!(story.created_at, Some(created));
+        assert_eq!(story.updated_at, Some(created + Duration::hours(1)));
+        assert_eq!(db_state.stories[&other_id].updated_at, Some(created));
+        assert_eq!(db_state.epics[&epic_id].updated_at, Some(created));
+
+        // undo brings back the old timestamps along with the rest of the item
+        db.undo().unwrap();
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].updated_at,
+            Some(created)
+        );
+    }
+
+    #[test]
+    fn create_should_write_empty_board_in_new_dir() {
+        let dir = tempfile::tempdir().unwrap();
+
+        for backend in [Backend::Json, Backend::Sqlite] {
+            let file_path = dir
+                .path()
+                .join(format!("{:?}/board", backend))
+                .to_str()
+                .unwrap()
+                .to_string();
+
+            JiraDatabase::create(backend, file_path.clone(), false).unwrap();
+
+            let db = JiraDatabase::open(backend, file_path).unwrap();
+            assert_eq!(db.read_db().unwrap(), empty_state());
+        }
+    }
+
+    #[test]
+    fn create_should_refuse_existing_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_string();
+        fs::write(&file_path, "keep me").unwrap();
+
+        let result = JiraDatabase::create(Backend::Json, file_path.clone(), false);
+
+        assert!(matches!(result, Err(JiraError::BoardExists(_))));
+        assert_eq!(fs::read_to_string(file_path).unwrap(), "keep me");
+    }
+
+    #[test]
+    fn create_should_back_up_board_it_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = json_db_in(&dir);
+        db.create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_string();
+        let before = fs::read_to_string(&file_path).unwrap();
+
+        JiraDatabase::create(Backend::Json, file_path.clone(), true).unwrap();
+
+        assert_eq!(db.read_db().unwrap(), empty_state());
+        assert!(matches!(db.undo(), Err(JiraError::NothingToUndo)));
+        let backups = backups::list(&file_path).unwrap();
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), before);
+    }
+
+    // stories go todo -> review -> done, epics keep the default workflow
+    fn review_workflows() -> Workflows {
+        serde_json::from_str(
+            r#"{ "story": {
+                "initial": "todo",
+                "states": [
+                    { "id": "todo", "name": "To Do", "category": "todo" },
+                    { "id": "review", "name": "Review", "category": "in_progress" },
+                    { "id": "done", "name": "Done", "category": "done" }
+                ],
+                "transitions": { "todo": ["review"], "review": ["todo", "done"] }
+            } }"#,
+        )
+        .unwrap()
+    }
+
+    fn json_db_in(dir: &tempfile::TempDir) -> JiraDatabase {
+        let file_path = dir.path().join("db.json").to_str().unwrap().to_string();
+        std::fs::write(
+            &file_path,
+            r#"{ "last_item_id": 0, "epics": {}, "stories": {} }"#,
+        )
+        .unwrap();
+        JiraDatabase::open(Backend::Json, file_path)
+            .unwrap()
+            .with_clock(Rc::new(FixedClock::default()))
+    }
+
+    fn sqlite_db_in(dir: &tempfile::TempDir) -> JiraDatabase {
+        let file_path = dir.path().join("db.sqlite").to_str().unwrap().to_string();
+        JiraDatabase::open(Backend::Sqlite, file_path)
+            .unwrap()
+            .with_clock(Rc::new(FixedClock::default()))
+    }
+
+    fn create_epic_should_work(db: JiraDatabase) {
+        let mut epic = Epic::new("".to_string(), "".to_string());
+
+        let result = db.create_epic(epic.clone());
+
+        assert!(result.is_ok());
+
+        let id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+
+        let expected_id = 1;
+
+        epic.created_at = Some(FixedClock::default().now());
+        epic.updated_at = epic.created_at;
+        assert_eq!(id, expected_id);
+        assert_eq!(db_state.last_item_id, expected_id);
+        assert_eq!(db_state.epics.get(&id), Some(&epic));
+    }
+
+    fn create_epic_should_error_if_next_id_is_taken(db: JiraDatabase) {
+        let epic = Epic::new("".to_string(), "".to_string());
+
+        let mut db_state = db.read_db().unwrap();
+        db_state.epics.insert(1, epic.clone());
+        db.database.write_db(&db_state).unwrap();
+
+        let result = db.create_epic(epic);
+        assert!(matches!(result, Err(JiraError::Conflict(_))));
+    }
+
+    fn create_story_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let story = Story::new("".to_string(), "".to_string());
+
+        let non_existent_epic_id = 9999;
+
+        let result = db.create_story(story, non_existent_epic_id);
+        assert!(matches!(result, Err(JiraError::EpicNotFound(9999))));
+    }
+
+    fn create_story_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_string(), "".to_string());
+        let mut story = Story::new("".to_string(), "".to_string());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story.clone(), epic_id);
+
+        let id = result.unwrap();
+        let db_state = db.read_db().unwrap();
+
+        let expected_id = 2;
+
+        assert_eq!(id, expected_id);
+        assert_eq!(db_state.last_item_id, expected_id);
+        story.created_at = Some(FixedClock::default().now());
+        story.updated_at = story.created_at;
+        assert!(db_state.epics.get(&epic_id).unwrap().stories.contains(&id));
+        assert_eq!(db_state.stories.get(&id), Some(&story));
+    }
+
+    fn delete_epic_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let non_existent_epic_id = 9999;
+
+        let result = db.delete_epic(non_existent_epic_id);
+        assert!(matches!(result, Err(JiraError::EpicNotFound(9999))));
+    }
+
+    fn delete_epic_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_string(), "".to_string());
+        let story = Story::new("".to_string(), "".to_string());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+
+        let story_id = result.unwrap();
+
+        let result = db.delete_epic(epic_id);
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+
+        let expected_last_id = 2;
+
+        assert_eq!(db_state.last_item_id, expected_last_id);
+        assert_eq!(db_state.epics.get(&epic_id), None);
+        assert_eq!(db_state.stories.get(&story_id), None);
+    }
+
+    fn delete_story_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let epic = Epic::new("".to_string(), "".to_string());
+        let story = Story::new("".to_string(), "".to_string());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+
+        let story_id = result.unwrap();
+        let non_existent_epic_id = 9999;
+
+        let result = db.delete_story(non_existent_epic_id, story_id);
+        assert!(matches!(result, Err(JiraError::EpicNotFound(9999))));
+    }
+
+    fn delete_story_should_error_if_story_not_found_in_epic(db: JiraDatabase) {
+        let epic = Epic::new("".to_string(), "".to_string());
+        let story = Story::new("".to_string(), "".to_string());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+
+        let non_existent_epic_id = 9999;
+
+        let result = db.delete_story(epic_id, non_existent_epic_id);
+        assert!(matches!(
+            result,
+            Err(JiraError::StoryNotInEpic { story_id: 9999, .. })
+        ));
+    }
+
+    fn delete_story_should_work(db: JiraDatabase) {
+        let story = Story::new("".to_string(), "".to_string());
+        let epic = Epic::new("".to_string(), "".to_string());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+
+        let story_id = result.unwrap();
+
+        let result = db.delete_story(epic_id, story_id);
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+
+        let expected_last_id = 2;
+
+        assert_eq!(db_state.last_item_id, expected_last_id);
+        assert!(!db_state
+            .epics
+            .get(&epic_id)
+            .unwrap()
+            .stories
+            .contains(&story_id));
+        assert_eq!(db_state.stories.get(&story_id), None);
+    }
+
+    fn update_epic_status_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let non_existent_epic_id = 9999;
+
+        let result = db.update_epic_status(non_existent_epic_id, Status::new("closed"));
+        assert!(matches!(result, Err(JiraError::EpicNotFound(9999))));
+    }
+
+    fn update_epic_status_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_string(), "".to_string());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.update_epic_status(epic_id, Status::new("closed"));
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(
+            db_state.epics.get(&epic_id).unwrap().status,
+            Status::new("closed")
+        );
+    }
+
+    fn update_epic_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("Tpyo".to_string(), "old".to_string()))
+            .unwrap();
+        db.update_epic_status(epic_id, Status::new("resolved"))
+            .unwrap();
+
+        let result = db.update_epic(
+            epic_id,
+            "Typo".to_string(),
+            "new".to_string(),
+            Priority::High,
+            NaiveDate::from_ymd_opt(2024, 3, 1),
+        );
+        assert!(result.is_ok());
+
+        let epic = &db.read_db().unwrap().epics[&epic_id];
+        assert_eq!(epic.due_date, NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(epic.name, "Typo");
+        assert_eq!(epic.description, "new");
+        assert_eq!(epic.priority, Priority::High);
+        assert_eq!(epic.status, Status::new("resolved"));
+    }
+
+    fn update_epic_should_error_if_invalid_epic_id(db: JiraDatabase) {
+        let result = db.update_epic(9999, "".to_string(), "".to_string(), Priority::Medium, None);
+        assert!(matches!(result, Err(JiraError::EpicNotFound(9999))));
+    }
+
+    fn update_story_should_work(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("Tpyo".to_string(), "old".to_string()), epic_id)
+            .unwrap();
+
+        let result = db.update_story(
+            story_id,
+            "Typo".to_string(),
+            "new".to_string(),
+            Priority::Lowest,
+            None,
+        );
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.stories[&story_id].name, "Typo");
+        assert_eq!(db_state.stories[&story_id].description, "new");
+        assert_eq!(db_state.stories[&story_id].priority, Priority::Lowest);
+        assert_eq!(db_state.epics[&epic_id].stories, vec![story_id]);
+    }
+
+    fn update_story_should_error_if_invalid_story_id(db: JiraDatabase) {
+        let result = db.update_story(9999, "".to_string(), "".to_string(), Priority::Medium, None);
+        assert!(matches!(result, Err(JiraError::StoryNotFound(9999))));
+    }
+
+    fn move_story_should_work(db: JiraDatabase) {
+        let from_epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let to_epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), from_epic_id)
+            .unwrap();
+
+        let result = db.move_story(story_id, from_epic_id, to_epic_id);
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert!(db_state.epics[&from_epic_id].stories.is_empty());
+        assert_eq!(db_state.epics[&to_epic_id].stories, vec![story_id]);
+        assert!(db_state.stories.contains_key(&story_id));
+    }
+
+    fn move_story_should_error_if_target_epic_missing(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let before = db.read_db().unwrap();
+
+        let result = db.move_story(story_id, epic_id, 9999);
+
+        assert!(matches!(result, Err(JiraError::EpicNotFound(9999))));
+        assert_eq!(db.read_db().unwrap(), before);
+    }
+
+    fn move_story_should_error_if_story_not_in_epic(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let other_epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        let result = db.move_story(story_id, other_epic_id, epic_id);
+
+        assert!(matches!(result, Err(JiraError::StoryNotInEpic { .. })));
+    }
+
+    fn add_user_should_refuse_duplicate_id(db: JiraDatabase) {
+        db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+            .unwrap();
+
+        let result = db.add_user("jdoe".to_string(), "John Doe".to_string());
+
+        assert!(matches!(result, Err(JiraError::UserExists(_))));
+        assert_eq!(db.read_db().unwrap().users["jdoe"].name, "Jane Doe");
+    }
+
+    fn assign_story_should_work(db: JiraDatabase) {
+        db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+            .unwrap();
+        db.add_user("rroe".to_string(), "Richard Roe".to_string())
+            .unwrap();
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        db.assign_story(story_id, Some("jdoe".to_string()), Some("rroe".to_string()))
+            .unwrap();
+
+        let story = &db.read_db().unwrap().stories[&story_id];
+        assert_eq!(story.assignee.as_deref(), Some("jdoe"));
+        assert_eq!(story.reporter.as_deref(), Some("rroe"));
+
+        db.undo().unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].assignee, None);
+    }
+
+    fn labels_should_keep_index_in_sync(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        assert!(db.add_epic_label(epic_id, "backend").unwrap());
+        assert!(db.add_story_label(story_id, "Backend").unwrap());
+        assert!(!db.add_story_label(story_id, "backend").unwrap());
+        assert!(db.add_story_label(story_id, "tech-debt").unwrap());
+
+        assert_eq!(
+            db.label_counts().unwrap(),
+            vec![("backend".to_string(), 2), ("tech-debt".to_string(), 1)]
+        );
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].labels,
+            ["backend", "tech-debt"]
+        );
+
+        assert!(db.remove_story_label(story_id, "tech-debt").unwrap());
+        assert!(!db.remove_epic_label(epic_id, "tech-debt").unwrap());
+        assert_eq!(db.label_counts().unwrap(), vec![("backend".to_string(), 2)]);
+
+        db.delete_story(epic_id, story_id).unwrap();
+        assert_eq!(
+            db.read_db().unwrap().labels,
+            BTreeMap::from([("backend".to_string(), vec![epic_id])])
+        );
+
+        db.undo().unwrap();
+        assert_eq!(db.label_counts().unwrap(), vec![("backend".to_string(), 2)]);
+    }
+
+    fn estimate_story_should_only_accept_points_on_scale(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        db.estimate_story(story_id, Some(8)).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].estimate, Some(8));
+
+        assert!(matches!(
+            db.estimate_story(story_id, Some(4)),
+            Err(JiraError::InvalidEstimate { estimate: 4, .. })
+        ));
+        db.estimate_story(story_id, None).unwrap();
+        assert_eq!(db.read_db().unwrap().stories[&story_id].estimate, None);
+        assert!(matches!(
+            db.estimate_story(999, None),
+            Err(JiraError::StoryNotFound(999))
+        ));
+    }
+
+    fn link_should_refuse_blocking_cycles(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let [a, b, c] = [(); 3].map(|_| {
+            db.create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap()
+        });
+
+        assert!(db.link(a, LinkKind::Blocks, b).unwrap());
+        assert!(db.link(b, LinkKind::Blocks, c).unwrap());
+        assert!(!db.link(a, LinkKind::Blocks, b).unwrap());
+
+        let cycle = format!("{c} -> {a} -> {b} -> {c}");
+        assert!(matches!(
+            db.link(c, LinkKind::Blocks, a),
+            Err(JiraError::BlockCycle(path)) if path == cycle
+        ));
+        // only blocks links have a direction that matters
+        assert!(db.link(c, LinkKind::RelatesTo, a).unwrap());
+        assert!(!db.link(a, LinkKind::RelatesTo, c).unwrap());
+        assert!(db.link(c, LinkKind::Duplicates, epic_id).unwrap());
+
+        assert!(matches!(
+            db.link(a, LinkKind::Blocks, a),
+            Err(JiraError::SelfLink(_))
+        ));
+        assert!(matches!(
+            db.link(a, LinkKind::Blocks, 999),
+            Err(JiraError::ItemNotFound(999))
+        ));
+
+        assert!(db.unlink(a, LinkKind::RelatesTo, c).unwrap());
+        assert!(!db.unlink(a, LinkKind::RelatesTo, c).unwrap());
+        assert!(db.unlink(b, LinkKind::Blocks, c).unwrap());
+        assert!(db.link(c, LinkKind::Blocks, a).unwrap());
+    }
+
+    fn open_blockers_should_skip_done_items(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let [blocked, open, closed] = [(); 3].map(|_| {
+            db.create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap()
+        });
+        db.update_story_status(closed, Status::new("closed"))
+            .unwrap();
+
+        for blocker in [epic_id, open, closed] {
+            db.link(blocker, LinkKind::Blocks, blocked).unwrap();
+        }
+        db.link(open, LinkKind::RelatesTo, closed).unwrap();
+
+        assert_eq!(db.open_blockers(blocked).unwrap(), [epic_id, open]);
+        assert!(db.open_blockers(open).unwrap().is_empty());
+    }
+
+    fn delete_story_should_remove_its_links(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let [a, b] = [(); 2].map(|_| {
+            db.create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap()
+        });
+        db.link(a, LinkKind::Blocks, b).unwrap();
+        db.link(b, LinkKind::RelatesTo, epic_id).unwrap();
+
+        db.delete_story(epic_id, b).unwrap();
+        assert!(db.read_db().unwrap().links.is_empty());
+
+        db.undo().unwrap();
+        assert_eq!(db.read_db().unwrap().links.len(), 2);
+    }
+
+    fn subtasks_should_keep_their_order(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let names = |db: &JiraDatabase| {
+            db.read_db().unwrap().stories[&story_id]
+                .subtasks
+                .iter()
+                .map(|subtask| subtask.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        for name in ["write", " test ", "ship"] {
+            db.add_subtask(story_id, name).unwrap();
+        }
+        assert!(matches!(
+            db.add_subtask(story_id, "  "),
+            Err(JiraError::EmptySubtask)
+        ));
+        assert_eq!(names(&db), ["write", "test", "ship"]);
+
+        assert!(db.toggle_subtask(story_id, 1).unwrap());
+        assert!(!db.toggle_subtask(story_id, 1).unwrap());
+        assert!(db.toggle_subtask(story_id, 0).unwrap());
+
+        db.move_subtask(story_id, 2, 0).unwrap();
+        assert_eq!(names(&db), ["ship", "write", "test"]);
+        assert!(db.read_db().unwrap().stories[&story_id].subtasks[1].done);
+
+        db.remove_subtask(story_id, 1).unwrap();
+        assert_eq!(names(&db), ["ship", "test"]);
+
+        assert!(matches!(
+            db.toggle_subtask(story_id, 2),
+            Err(JiraError::SubtaskNotFound { index: 2, .. })
+        ));
+        assert!(matches!(
+            db.move_subtask(story_id, 0, 5),
+            Err(JiraError::SubtaskNotFound { index: 5, .. })
+        ));
+        assert!(matches!(
+            db.remove_subtask(999, 0),
+            Err(JiraError::StoryNotFound(999))
+        ));
+
+        db.undo().unwrap();
+        assert_eq!(names(&db), ["ship", "write", "test"]);
+    }
+
+    fn comments_should_only_be_changed_by_their_author(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+            .unwrap();
+        db.add_user("rroe".to_string(), "Richard Roe".to_string())
+            .unwrap();
+
+        let first = db
+            .add_comment(story_id, "jdoe".to_string(), " looks good ".to_string())
+            .unwrap();
+        let second = db
+            .add_comment(story_id, "rroe".to_string(), "agreed".to_string())
+            .unwrap();
+        assert_eq!((first, second), (1, 2));
+        assert!(matches!(
+            db.add_comment(story_id, "nobody".to_string(), "hi".to_string()),
+            Err(JiraError::UserNotFound(_))
+        ));
+        assert!(matches!(
+            db.add_comment(story_id, "jdoe".to_string(), "  ".to_string()),
+            Err(JiraError::EmptyComment)
+        ));
+
+        assert!(matches!(
+            db.edit_comment(story_id, first, "rroe", "changed".to_string()),
+            Err(JiraError::NotCommentAuthor(1))
+        ));
+        db.edit_comment(story_id, first, "jdoe", "ship it".to_string())
+            .unwrap();
+
+        let comments = &db.read_db().unwrap().comments[&story_id];
+        assert_eq!(comments[0].body, "ship it");
+        assert!(comments[0].edited_at.is_some());
+        assert_eq!(comments[1].edited_at, None);
+
+        assert!(matches!(
+            db.delete_comment(story_id, second, "jdoe"),
+            Err(JiraError::NotCommentAuthor(2))
+        ));
+        db.delete_comment(story_id, second, "rroe").unwrap();
+        assert!(matches!(
+            db.delete_comment(story_id, second, "rroe"),
+            Err(JiraError::CommentNotFound { comment_id: 2, .. })
+        ));
+        assert_eq!(db.read_db().unwrap().comments[&story_id].len(), 1);
+    }
+
+    fn delete_story_should_remove_its_comments(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+            .unwrap();
+        db.add_comment(story_id, "jdoe".to_string(), "hi".to_string())
+            .unwrap();
+
+        db.delete_epic(epic_id).unwrap();
+        assert!(db.read_db().unwrap().comments.is_empty());
+
+        db.undo().unwrap();
+        assert_eq!(db.read_db().unwrap().comments[&story_id].len(), 1);
+    }
+
+    fn add_label_should_reject_invalid_label(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+
+        assert!(matches!(
+            db.add_epic_label(epic_id, "tech debt"),
+            Err(JiraError::InvalidLabel(_))
+        ));
+        assert!(matches!(
+            db.add_story_label(999, "backend"),
+            Err(JiraError::StoryNotFound(999))
+        ));
+    }
+
+    fn assign_should_require_known_users(db: JiraDatabase) {
+        let mut epic = Epic::new("".to_string(), "".to_string());
+        epic.reporter = Some("ghost".to_string());
+        assert!(matches!(
+            db.create_epic(epic),
+            Err(JiraError::UserNotFound(_))
+        ));
+
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let result = db.assign_epic(epic_id, Some("ghost".to_string()), None);
+
+        assert!(matches!(result, Err(JiraError::UserNotFound(id)) if id == "ghost"));
+        assert_eq!(db.read_db().unwrap().epics[&epic_id].assignee, None);
+    }
+
+    fn update_story_status_should_error_if_invalid_story_id(db: JiraDatabase) {
+        let non_existent_story_id = 9999;
+
+        let result = db.update_story_status(non_existent_story_id, Status::new("closed"));
+        assert!(matches!(result, Err(JiraError::StoryNotFound(9999))));
+    }
+
+    fn update_story_status_should_work(db: JiraDatabase) {
+        let epic = Epic::new("".to_string(), "".to_string());
+        let story = Story::new("".to_string(), "".to_string());
+
+        let result = db.create_epic(epic);
+        assert!(result.is_ok());
+
+        let epic_id = result.unwrap();
+
+        let result = db.create_story(story, epic_id);
+        assert!(result.is_ok());
+
+        let story_id = result.unwrap();
+
+        let result = db.update_story_status(story_id, Status::new("closed"));
+        assert!(result.is_ok());
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(
+            db_state.stories.get(&story_id).unwrap().status,
+            Status::new("closed"),
+        );
+    }
+
+    fn create_should_start_in_initial_state(db: JiraDatabase) {
+        let db = db.with_workflows(review_workflows());
+
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&epic_id].status, Status::new("open"));
+        assert_eq!(db_state.stories[&story_id].status, Status::new("todo"));
+    }
+
+    fn update_story_status_should_reject_illegal_transition(db: JiraDatabase) {
+        let db = db.with_workflows(review_workflows());
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        let result = db.update_story_status(story_id, Status::new("done"));
+
+        assert!(matches!(result, Err(JiraError::IllegalTransition { .. })));
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::new("todo")
+        );
+
+        db.update_story_status(story_id, Status::new("review"))
+            .unwrap();
+        db.update_story_status(story_id, Status::new("done"))
+            .unwrap();
+        db.update_story_status(story_id, Status::new("done"))
+            .unwrap();
+    }
+
+    fn update_epic_status_should_block_done_with_unfinished_stories(db: JiraDatabase) {
+        let mut workflows = Workflows::default();
+        workflows.roll_up.block_done = true;
+        let db = db.with_workflows(workflows);
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        let result = db.update_epic_status(epic_id, Status::new("closed"));
+        assert!(matches!(
+            result,
+            Err(JiraError::UnfinishedStories { open: 1, .. })
+        ));
+        db.update_epic_status(epic_id, Status::new("in_progress"))
+            .unwrap();
+
+        db.update_story_status(story_id, Status::new("resolved"))
+            .unwrap();
+        db.update_epic_status(epic_id, Status::new("closed"))
+            .unwrap();
+        assert_eq!(
+            db.read_db().unwrap().epics[&epic_id].status,
+            Status::new("closed")
+        );
+    }
+
+    fn update_story_status_should_start_epic(db: JiraDatabase) {
+        let mut workflows = Workflows::default();
+        workflows.roll_up.auto_start = true;
+        let db = db.with_workflows(workflows);
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        db.update_story_status(story_id, Status::new("in_progress"))
+            .unwrap();
+        assert_eq!(
+            db.read_db().unwrap().epics[&epic_id].status,
+            Status::new("in_progress")
+        );
+
+        // one change, one undo step
+        db.undo().unwrap();
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&epic_id].status, Status::new("open"));
+        assert_eq!(db_state.stories[&story_id].status, Status::new("open"));
+
+        // a finished epic is left alone
+        db.update_epic_status(epic_id, Status::new("resolved"))
+            .unwrap();
+        db.update_story_status(story_id, Status::new("in_progress"))
+            .unwrap();
+        assert_eq!(
+            db.read_db().unwrap().epics[&epic_id].status,
+            Status::new("resolved")
+        );
+    }
+
+    fn transaction_should_commit_every_change(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+
+        let result = db.transaction(|db_state| {
+            let epic = db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?;
+            epic.status = Status::new("in_progress");
+            epic.name = "renamed".to_string();
+
+            db_state
+                .stories
+                .get_mut(&story_id)
+                .ok_or(JiraError::StoryNotFound(story_id))?
+                .status = Status::new("closed");
+
+            Ok(epic_id)
+        });
+        assert_eq!(result.unwrap(), epic_id);
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state.epics[&epic_id].status, Status::new("in_progress"));
+        assert_eq!(db_state.epics[&epic_id].name, "renamed");
+        assert_eq!(db_state.stories[&story_id].status, Status::new("closed"));
+    }
+
+    fn transaction_should_roll_back_on_error(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let before = db.read_db().unwrap();
+
+        let result: Result<()> = db.transaction(|db_state| {
+            db_state
+                .epics
+                .get_mut(&epic_id)
+                .ok_or(JiraError::EpicNotFound(epic_id))?
+                .status = Status::new("closed");
+
+            db_state
+                .stories
+                .get_mut(&999)
+                .ok_or(JiraError::StoryNotFound(999))?
+                .status = Status::new("closed");
+
+            Ok(())
+        });
+
+        assert!(matches!(result, Err(JiraError::StoryNotFound(999))));
+        assert_eq!(db.read_db().unwrap(), before);
+    }
+
+    fn undo_should_restore_previous_state(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let before_delete = db.read_db().unwrap();
+
+        db.delete_epic(epic_id).unwrap();
+        db.undo().unwrap();
+
+        let db_state = db.read_db().unwrap();
+        assert_eq!(db_state, before_delete);
+        assert!(db_state.stories.contains_key(&story_id));
+    }
+
+    fn undo_should_error_when_history_is_empty(db: JiraDatabase) {
+        assert!(matches!(db.undo(), Err(JiraError::NothingToUndo)));
+        assert!(matches!(db.redo(), Err(JiraError::NothingToRedo)));
+    }
+
+    fn redo_should_reapply_undone_change(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        db.update_epic_status(epic_id, Status::new("closed"))
+            .unwrap();
+        let after_update = db.read_db().unwrap();
+
+        db.undo().unwrap();
+        assert_eq!(
+            db.read_db().unwrap().epics[&epic_id].status,
+            Status::new("open")
+        );
+
+        db.redo().unwrap();
+        assert_eq!(db.read_db().unwrap(), after_update);
+    }
+
+    fn redo_should_be_cleared_by_new_change(db: JiraDatabase) {
+        db.create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        db.undo().unwrap();
+        db.create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+
+        assert!(matches!(db.redo(), Err(JiraError::NothingToRedo)));
+    }
+
+    fn history_should_be_limited_to_depth(db: JiraDatabase) {
+        let db = db.with_history_depth(2);
+
+        for _ in 0..3 {
+            db.create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+        }
+
+        db.undo().unwrap();
+        db.undo().unwrap();
+        assert!(matches!(db.undo(), Err(JiraError::NothingToUndo)));
+        assert_eq!(db.read_db().unwrap().epics.len(), 1);
+    }
+
+    fn history_should_not_record_failed_transactions(db: JiraDatabase) {
+        assert!(db.delete_epic(999).is_err());
+        assert!(matches!(db.undo(), Err(JiraError::NothingToUndo)));
+    }
+
+    fn repair_should_fix_dangling_story(db: JiraDatabase) {
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let mut db_state = db.read_db().unwrap();
+        db_state.epics.get_mut(&epic_id).unwrap().stories.push(99);
+        db.database.write_db(&db_state).unwrap();
+
+        assert_eq!(db.check().unwrap().len(), 1);
+        assert_eq!(db.repair().unwrap().len(), 1);
+
+        assert_eq!(db.check().unwrap(), vec![]);
+        assert!(db.read_db().unwrap().epics[&epic_id].stories.is_empty());
+    }
+
+    mod failing_writes {
+        use super::*;
+
+        #[test]
+        fn create_epic_should_error_if_write_fails() {
+            let db = failing_db();
+
+            let result = db.create_epic(Epic::new("".to_string(), "".to_string()));
+            assert!(matches!(result, Err(JiraError::Io(_))));
+        }
+
+        #[test]
+        fn create_story_should_error_if_write_fails() {
+            let db = failing_db();
+
+            let result = db.create_story(Story::new("".to_string(), "".to_string()), 1);
+            assert!(matches!(result, Err(JiraError::Io(_))));
+        }
+
+        #[test]
+        fn delete_epic_should_error_if_write_fails() {
+            let db = failing_db();
+
+            let result = db.delete_epic(1);
+            assert!(matches!(result, Err(JiraError::Io(_))));
+        }
+
+        #[test]
+        fn delete_story_should_error_if_write_fails() {
+            let db = failing_db();
+
+            let result = db.delete_story(1, 2);
+            assert!(matches!(result, Err(JiraError::Io(_))));
+        }
+
+        #[test]
+        fn update_epic_status_should_error_if_write_fails() {
+            let db = failing_db();
+
+            let result = db.update_epic_status(1, Status::new("closed"));
+            assert!(matches!(result, Err(JiraError::Io(_))));
+        }
+
+        #[test]
+        fn update_story_status_should_error_if_write_fails() {
+            let db = failing_db();
+
+            let result = db.update_story_status(2, Status::new("closed"));
+            assert!(matches!(result, Err(JiraError::Io(_))));
+        }
+
+        // epic 1 holding story 2, backed by a database that rejects every write
+        fn failing_db() -> JiraDatabase {
+            let db = JiraDatabase::new(Box::new(MockDB::new()));
+            let epic_id = db
+                .create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+            db.create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap();
+
+            JiraDatabase::new(Box::new(FailingDB::new(db.read_db().unwrap())))
+        }
+    }
+}
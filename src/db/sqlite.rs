@@ -0,0 +1,929 @@
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use super::{
+    decode_history, labels,
+    migrations::{CURRENT_SCHEMA_VERSION, LEGACY_STATUSES},
+    Database,
+};
+use crate::{
+    error::{JiraError, Result},
+    models::{Comment, DBState, Epic, History, Link, Story, Subtask, User},
+};
+
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// id, name, description, status, priority, assignee, reporter, due_date, created_at, updated_at
+type ItemRow = (
+    u32,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS epics (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS stories (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        status TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS epic_stories (
+        epic_id INTEGER NOT NULL,
+        story_id INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (epic_id, story_id)
+    );
+    CREATE TABLE IF NOT EXISTS item_labels (
+        item_id INTEGER NOT NULL,
+        label TEXT NOT NULL,
+        position INTEGER NOT NULL,
+        PRIMARY KEY (item_id, label)
+    );
+    CREATE TABLE IF NOT EXISTS subtasks (
+        story_id INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        done INTEGER NOT NULL,
+        PRIMARY KEY (story_id, position)
+    );
+    CREATE TABLE IF NOT EXISTS comments (
+        story_id INTEGER NOT NULL,
+        id INTEGER NOT NULL,
+        author TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        body TEXT NOT NULL,
+        edited_at TEXT,
+        PRIMARY KEY (story_id, id)
+    );
+    CREATE TABLE IF NOT EXISTS links (
+        from_id INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        to_id INTEGER NOT NULL,
+        PRIMARY KEY (from_id, position)
+    );
+    CREATE TABLE IF NOT EXISTS users (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS history (
+        id INTEGER PRIMARY KEY CHECK (id = 1),
+        document TEXT NOT NULL
+    );
+";
+
+pub struct SqliteDatabase {
+    connection: Connection,
+    // set while `transaction` holds an open sqlite transaction
+    in_transaction: Cell<bool>,
+}
+
+impl SqliteDatabase {
+    pub fn open(file_path: &str) -> Result<Self> {
+        let connection = Connection::open(file_path)?;
+        connection.busy_timeout(BUSY_TIMEOUT)?;
+        connection.execute_batch(SCHEMA)?;
+
+        // columns added after the tables were first created
+        for table in ["epics", "stories"] {
+            for column in [
+                "assignee",
+                "reporter",
+                "due_date",
+                "created_at",
+                "updated_at",
+            ] {
+                add_column(&connection, table, column, "TEXT")?;
+            }
+            add_column(
+                &connection,
+                table,
+                "priority",
+                "TEXT NOT NULL DEFAULT 'medium'",
+            )?;
+        }
+        add_column(&connection, "stories", "estimate", "INTEGER")?;
+
+        // rows written before workflows existed hold the names of the old Status variants
+        for (legacy, id) in LEGACY_STATUSES {
+            for table in ["epics", "stories"] {
+                connection.execute(
+                    &format!("UPDATE {table} SET status = ?1 WHERE status = ?2"),
+                    params![id, legacy],
+                )?;
+            }
+        }
+
+        Ok(Self {
+            connection,
+            in_transaction: Cell::new(false),
+        })
+    }
+
+    // nothing has ever been written to it
+    pub fn is_new(&self) -> Result<bool> {
+        Ok(self.last_item_id()?.is_none())
+    }
+
+    pub fn import(&self, source: &dyn Database) -> Result<()> {
+        self.write_db(&source.read_db()?)
+    }
+
+    fn last_item_id(&self) -> Result<Option<u32>> {
+        Ok(self
+            .connection
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'last_item_id'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn read_state(&self) -> Result<DBState> {
+        let mut epics = HashMap::new();
+        let mut statement = self.connection.prepare(
+            "SELECT id, name, description, status, priority, assignee, reporter, due_date, \
+             created_at, updated_at FROM epics",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+                row.get(7)?,
+                row.get(8)?,
+                row.get(9)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (
+                id,
+                name,
+                description,
+                status,
+                priority,
+                assignee,
+                reporter,
+                due_date,
+                created_at,
+                updated_at,
+            ): ItemRow = row?;
+            epics.insert(
+                id,
+                Epic {
+                    name,
+                    description,
+                    status: from_sql(status)?,
+                    priority: from_sql(priority)?,
+                    stories: vec![],
+                    assignee,
+                    reporter,
+                    labels: vec![],
+                    due_date: due_date.map(from_sql).transpose()?,
+                    created_at: created_at.map(from_sql).transpose()?,
+                    updated_at: updated_at.map(from_sql).transpose()?,
+                },
+            );
+        }
+
+        let mut statement = self
+            .connection
+            .prepare("SELECT epic_id, story_id FROM epic_stories ORDER BY epic_id, position")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        for row in rows {
+            let (epic_id, story_id): (u32, u32) = row?;
+            epics
+                .get_mut(&epic_id)
+                .ok_or_else(|| {
+                    JiraError::Corrupt(format!(
+                        "story {story_id} belongs to missing epic {epic_id}"
+                    ))
+                })?
+                .stories
+                .push(story_id);
+        }
+
+        let mut stories = HashMap::new();
+        let mut statement = self.connection.prepare(
+            "SELECT id, name, description, status, priority, assignee, reporter, due_date, \
+             created_at, updated_at, estimate FROM stories",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                (
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                    row.get(9)?,
+                ),
+                row.get(10)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (
+                (
+                    id,
+                    name,
+                    description,
+                    status,
+                    priority,
+                    assignee,
+                    reporter,
+                    due_date,
+                    created_at,
+                    updated_at,
+                ),
+                estimate,
+            ): (ItemRow, Option<u32>) = row?;
+            stories.insert(
+                id,
+                Story {
+                    name,
+                    description,
+                    status: from_sql(status)?,
+                    priority: from_sql(priority)?,
+                    assignee,
+                    reporter,
+                    labels: vec![],
+                    due_date: due_date.map(from_sql).transpose()?,
+                    estimate,
+                    subtasks: vec![],
+                    created_at: created_at.map(from_sql).transpose()?,
+                    updated_at: updated_at.map(from_sql).transpose()?,
+                },
+            );
+        }
+
+        // epics and stories share one id space, so their labels share one table
+        let mut statement = self
+            .connection
+            .prepare("SELECT item_id, label FROM item_labels ORDER BY item_id, position")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        for row in rows {
+            let (item_id, label): (u32, String) = row?;
+            let item_labels = match (epics.get_mut(&item_id), stories.get_mut(&item_id)) {
+                (Some(epic), _) => &mut epic.labels,
+                (None, Some(story)) => &mut story.labels,
+                (None, None) => {
+                    return Err(JiraError::Corrupt(format!(
+                        "label {label} belongs to missing item {item_id}"
+                    )))
+                }
+            };
+            item_labels.push(label);
+        }
+
+        let mut statement = self
+            .connection
+            .prepare("SELECT story_id, name, done FROM subtasks ORDER BY story_id, position")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+        for row in rows {
+            let (story_id, name, done): (u32, String, bool) = row?;
+            let story = stories.get_mut(&story_id).ok_or_else(|| {
+                JiraError::Corrupt(format!(
+                    "subtask {name} belongs to missing story {story_id}"
+                ))
+            })?;
+            story.subtasks.push(Subtask { name, done });
+        }
+
+        let mut users = HashMap::new();
+        let mut statement = self.connection.prepare("SELECT id, name FROM users")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        for row in rows {
+            let (id, name): (String, String) = row?;
+            users.insert(id, User { name });
+        }
+
+        // the tables are rebuilt into the current document shape no matter what wrote them
+        let mut db_state = DBState {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            last_item_id: self.last_item_id()?.unwrap_or(0),
+            epics,
+            stories,
+            users,
+            labels: BTreeMap::new(),
+            comments: self.read_comments()?,
+            links: self.read_links()?,
+        };
+        // the index is derived, so it is never stored
+        db_state.labels = labels::index(&db_state);
+
+        Ok(db_state)
+    }
+
+    // only rows that differ from what is stored get touched
+    fn write_state(&self, db_state: &DBState) -> Result<()> {
+        let stored = self.read_state()?;
+
+        self.connection.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('last_item_id', ?1)",
+            params![db_state.last_item_id],
+        )?;
+
+        for (id, epic) in &db_state.epics {
+            if stored.epics.get(id) == Some(epic) {
+                continue;
+            }
+
+            self.connection.execute(
+                "INSERT OR REPLACE INTO epics \
+                 (id, name, description, status, priority, assignee, reporter, due_date, \
+                 created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    id,
+                    epic.name,
+                    epic.description,
+                    to_sql(&epic.status)?,
+                    to_sql(&epic.priority)?,
+                    epic.assignee,
+                    epic.reporter,
+                    epic.due_date.as_ref().map(to_sql).transpose()?,
+                    epic.created_at.as_ref().map(to_sql).transpose()?,
+                    epic.updated_at.as_ref().map(to_sql).transpose()?
+                ],
+            )?;
+            self.connection
+                .execute("DELETE FROM epic_stories WHERE epic_id = ?1", params![id])?;
+
+            for (position, story_id) in epic.stories.iter().enumerate() {
+                self.connection.execute(
+                    "INSERT INTO epic_stories (epic_id, story_id, position) VALUES (?1, ?2, ?3)",
+                    params![id, story_id, position],
+                )?;
+            }
+            self.write_labels(*id, &epic.labels)?;
+        }
+
+        for id in stored.epics.keys() {
+            if !db_state.epics.contains_key(id) {
+                self.connection
+                    .execute("DELETE FROM epics WHERE id = ?1", params![id])?;
+                self.connection
+                    .execute("DELETE FROM epic_stories WHERE epic_id = ?1", params![id])?;
+                self.write_labels(*id, &[])?;
+            }
+        }
+
+        for (id, story) in &db_state.stories {
+            if stored.stories.get(id) == Some(story) {
+                continue;
+            }
+
+            self.connection.execute(
+                "INSERT OR REPLACE INTO stories \
+                 (id, name, description, status, priority, assignee, reporter, due_date, \
+                 created_at, updated_at, estimate) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    id,
+                    story.name,
+                    story.description,
+                    to_sql(&story.status)?,
+                    to_sql(&story.priority)?,
+                    story.assignee,
+                    story.reporter,
+                    story.due_date.as_ref().map(to_sql).transpose()?,
+                    story.created_at.as_ref().map(to_sql).transpose()?,
+                    story.updated_at.as_ref().map(to_sql).transpose()?,
+                    story.estimate
+                ],
+            )?;
+            self.write_labels(*id, &story.labels)?;
+            self.write_subtasks(*id, &story.subtasks)?;
+        }
+
+        for id in stored.stories.keys() {
+            if !db_state.stories.contains_key(id) {
+                self.connection
+                    .execute("DELETE FROM stories WHERE id = ?1", params![id])?;
+                self.write_labels(*id, &[])?;
+                self.write_subtasks(*id, &[])?;
+            }
+        }
+
+        for (story_id, comments) in &db_state.comments {
+            if stored.comments.get(story_id) != Some(comments) {
+                self.write_comments(*story_id, comments)?;
+            }
+        }
+
+        for story_id in stored.comments.keys() {
+            if !db_state.comments.contains_key(story_id) {
+                self.write_comments(*story_id, &[])?;
+            }
+        }
+
+        for (from, links) in &db_state.links {
+            if stored.links.get(from) != Some(links) {
+                self.write_links(*from, links)?;
+            }
+        }
+
+        for from in stored.links.keys() {
+            if !db_state.links.contains_key(from) {
+                self.write_links(*from, &[])?;
+            }
+        }
+
+        for (id, user) in &db_state.users {
+            if stored.users.get(id) == Some(user) {
+                continue;
+            }
+
+            self.connection.execute(
+                "INSERT OR REPLACE INTO users (id, name) VALUES (?1, ?2)",
+                params![id, user.name],
+            )?;
+        }
+
+        for id in stored.users.keys() {
+            if !db_state.users.contains_key(id) {
+                self.connection
+                    .execute("DELETE FROM users WHERE id = ?1", params![id])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_comments(&self) -> Result<HashMap<u32, Vec<Comment>>> {
+        let mut comments: HashMap<u32, Vec<Comment>> = HashMap::new();
+        let mut statement = self.connection.prepare(
+            "SELECT story_id, id, author, created_at, body, edited_at FROM comments \
+             ORDER BY story_id, id",
+        )?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (story_id, id, author, created_at, body, edited_at): (
+                u32,
+                u32,
+                String,
+                String,
+                String,
+                Option<String>,
+            ) = row?;
+            comments.entry(story_id).or_default().push(Comment {
+                id,
+                author,
+                created_at: from_sql(created_at)?,
+                body,
+                edited_at: edited_at.map(from_sql).transpose()?,
+            });
+        }
+
+        Ok(comments)
+    }
+
+    fn write_comments(&self, story_id: u32, comments: &[Comment]) -> Result<()> {
+        self.connection.execute(
+            "DELETE FROM comments WHERE story_id = ?1",
+            params![story_id],
+        )?;
+
+        for comment in comments {
+            self.connection.execute(
+                "INSERT INTO comments (story_id, id, author, created_at, body, edited_at) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    story_id,
+                    comment.id,
+                    comment.author,
+                    to_sql(&comment.created_at)?,
+                    comment.body,
+                    comment.edited_at.as_ref().map(to_sql).transpose()?
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn read_links(&self) -> Result<HashMap<u32, Vec<Link>>> {
+        let mut links: HashMap<u32, Vec<Link>> = HashMap::new();
+        let mut statement = self
+            .connection
+            .prepare("SELECT from_id, kind, to_id FROM links ORDER BY from_id, position")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+
+        for row in rows {
+            let (from, kind, to): (u32, String, u32) = row?;
+            links.entry(from).or_default().push(Link {
+                kind: from_sql(kind)?,
+                to,
+            });
+        }
+
+        Ok(links)
+    }
+
+    fn write_links(&self, from: u32, links: &[Link]) -> Result<()> {
+        self.connection
+            .execute("DELETE FROM links WHERE from_id = ?1", params![from])?;
+
+        for (position, link) in links.iter().enumerate() {
+            self.connection.execute(
+                "INSERT INTO links (from_id, position, kind, to_id) VALUES (?1, ?2, ?3, ?4)",
+                params![from, position, to_sql(&link.kind)?, link.to],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_subtasks(&self, story_id: u32, subtasks: &[Subtask]) -> Result<()> {
+        self.connection.execute(
+            "DELETE FROM subtasks WHERE story_id = ?1",
+            params![story_id],
+        )?;
+
+        for (position, subtask) in subtasks.iter().enumerate() {
+            self.connection.execute(
+                "INSERT INTO subtasks (story_id, position, name, done) VALUES (?1, ?2, ?3, ?4)",
+                params![story_id, position, subtask.name, subtask.done],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn write_labels(&self, item_id: u32, item_labels: &[String]) -> Result<()> {
+        self.connection.execute(
+            "DELETE FROM item_labels WHERE item_id = ?1",
+            params![item_id],
+        )?;
+
+        for (position, label) in item_labels.iter().enumerate() {
+            self.connection.execute(
+                "INSERT INTO item_labels (item_id, label, position) VALUES (?1, ?2, ?3)",
+                params![item_id, label, position],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Database for SqliteDatabase {
+    fn read_db(&self) -> Result<DBState> {
+        if self.in_transaction.get() {
+            return self.read_state();
+        }
+
+        // one transaction so the tables are read from the same snapshot
+        let transaction = self.connection.unchecked_transaction()?;
+        let db_state = self.read_state()?;
+        transaction.commit()?;
+
+        Ok(db_state)
+    }
+
+    fn write_db(&self, db_state: &DBState) -> Result<()> {
+        if self.in_transaction.get() {
+            return self.write_state(db_state);
+        }
+
+        let transaction = self.connection.unchecked_transaction()?;
+        self.write_state(db_state)?;
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    fn read_history(&self) -> Result<History> {
+        let document: Option<String> = self
+            .connection
+            .query_row("SELECT document FROM history WHERE id = 1", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+
+        match document {
+            Some(document) => decode_history(&document),
+            None => Ok(History::default()),
+        }
+    }
+
+    fn write_history(&self, history: &History) -> Result<()> {
+        self.connection.execute(
+            "INSERT OR REPLACE INTO history (id, document) VALUES (1, ?1)",
+            params![serde_json::to_string(history)?],
+        )?;
+        Ok(())
+    }
+
+    fn transaction(&self, f: &mut dyn FnMut(&mut DBState) -> Result<()>) -> Result<()> {
+        if self.in_transaction.get() {
+            let mut db_state = self.read_state()?;
+            f(&mut db_state)?;
+            return self.write_state(&db_state);
+        }
+
+        // IMMEDIATE takes sqlite's write lock up front instead of on the first write
+        self.connection.execute_batch("BEGIN IMMEDIATE")?;
+        self.in_transaction.set(true);
+        let result = self.read_state().and_then(|mut db_state| {
+            f(&mut db_state)?;
+            self.write_state(&db_state)
+        });
+        self.in_transaction.set(false);
+
+        match result {
+            Ok(()) => Ok(self.connection.execute_batch("COMMIT")?),
+            Err(error) => {
+                let _ = self.connection.execute_batch("ROLLBACK");
+                Err(error)
+            }
+        }
+    }
+}
+
+fn add_column(connection: &Connection, table: &str, column: &str, kind: &str) -> Result<()> {
+    let exists: bool = connection.query_row(
+        &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{table}') WHERE name = ?1"),
+        params![column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        connection.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {kind}"))?;
+    }
+    Ok(())
+}
+
+// statuses, priorities, dates and link kinds are stored as the same string serde writes into db.json
+fn to_sql<T: Serialize>(value: &T) -> Result<String> {
+    match serde_json::to_value(value)? {
+        Value::String(value) => Ok(value),
+        other => Err(JiraError::Corrupt(format!("unexpected value {other}"))),
+    }
+}
+
+fn from_sql<T: DeserializeOwned>(value: String) -> Result<T> {
+    Ok(serde_json::from_value(Value::String(value))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use super::*;
+    use crate::db::{json_file::JSONFileDatabase, Backend, JiraDatabase};
+    use crate::models::{LinkKind, Priority, Status};
+
+    #[test]
+    fn read_db_should_return_empty_state_for_new_database() {
+        let db = SqliteDatabase::open(":memory:").unwrap();
+
+        let db_state = db.read_db().unwrap();
+
+        assert!(db.is_new().unwrap());
+        assert_eq!(db_state.last_item_id, 0);
+        assert!(db_state.epics.is_empty());
+        assert!(db_state.stories.is_empty());
+    }
+
+    #[test]
+    fn write_db_should_work() {
+        let db = SqliteDatabase::open(":memory:").unwrap();
+        let state = sample_state();
+
+        db.write_db(&state).unwrap();
+
+        assert!(!db.is_new().unwrap());
+        assert_eq!(db.read_db().unwrap(), state);
+    }
+
+    #[test]
+    fn write_db_should_remove_deleted_items() {
+        let db = SqliteDatabase::open(":memory:").unwrap();
+        let mut state = sample_state();
+        db.write_db(&state).unwrap();
+
+        state.epics.remove(&1);
+        state.stories.remove(&2);
+        db.write_db(&state).unwrap();
+
+        let epic_story_rows: u32 = db
+            .connection
+            .query_row("SELECT COUNT(*) FROM epic_stories", [], |row| row.get(0))
+            .unwrap();
+        let label_rows: u32 = db
+            .connection
+            .query_row("SELECT COUNT(*) FROM item_labels", [], |row| row.get(0))
+            .unwrap();
+
+        state.labels.clear();
+        assert_eq!(db.read_db().unwrap(), state);
+        assert_eq!(epic_story_rows, 0);
+        assert_eq!(label_rows, 0);
+    }
+
+    #[test]
+    fn write_db_should_keep_story_order() {
+        let db = SqliteDatabase::open(":memory:").unwrap();
+        let mut state = sample_state();
+        state
+            .stories
+            .insert(3, Story::new("story 3 name".to_string(), "".to_string()));
+        state.epics.get_mut(&1).unwrap().stories = vec![3, 2];
+        state.last_item_id = 3;
+
+        db.write_db(&state).unwrap();
+
+        assert_eq!(db.read_db().unwrap().epics[&1].stories, vec![3, 2]);
+    }
+
+    #[test]
+    fn open_should_add_columns_to_old_tables() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite_path = dir.path().join("db.sqlite").to_str().unwrap().to_string();
+        let connection = Connection::open(&sqlite_path).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE epics (id INTEGER PRIMARY KEY, name TEXT NOT NULL, \
+                 description TEXT NOT NULL, status TEXT NOT NULL);
+                 INSERT INTO epics VALUES (1, 'old', '', 'open');",
+            )
+            .unwrap();
+        drop(connection);
+
+        let db = SqliteDatabase::open(&sqlite_path).unwrap();
+
+        let epic = &db.read_db().unwrap().epics[&1];
+        assert_eq!(epic.name, "old");
+        assert_eq!(epic.priority, Priority::Medium);
+        assert_eq!(epic.assignee, None);
+        db.write_db(&sample_state()).unwrap();
+        assert_eq!(db.read_db().unwrap(), sample_state());
+    }
+
+    #[test]
+    fn transaction_should_roll_back_on_error() {
+        let db = SqliteDatabase::open(":memory:").unwrap();
+
+        let result = db.transaction(&mut |db_state| {
+            *db_state = sample_state();
+            db.write_db(db_state)?;
+            Err(JiraError::EpicNotFound(1))
+        });
+
+        assert!(matches!(result, Err(JiraError::EpicNotFound(1))));
+        assert!(db.is_new().unwrap());
+    }
+
+    #[test]
+    fn new_should_import_sibling_json_file_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let json_path = dir.path().join("db.json").to_str().unwrap().to_string();
+        let sqlite_path = dir.path().join("db.sqlite").to_str().unwrap().to_string();
+        JSONFileDatabase::new(json_path.clone())
+            .unwrap()
+            .write_db(&sample_state())
+            .unwrap();
+
+        let db = JiraDatabase::open(Backend::Sqlite, sqlite_path.clone()).unwrap();
+        assert_eq!(db.read_db().unwrap(), sample_state());
+
+        db.delete_epic(1).unwrap();
+        drop(db);
+
+        // the json file is only read while the sqlite file is still empty
+        let db = JiraDatabase::open(Backend::Sqlite, sqlite_path).unwrap();
+        assert!(db.read_db().unwrap().epics.is_empty());
+    }
+
+    #[test]
+    fn history_should_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let sqlite_path = dir.path().join("db.sqlite").to_str().unwrap().to_string();
+
+        let db = JiraDatabase::open(Backend::Sqlite, sqlite_path.clone()).unwrap();
+        db.create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        drop(db);
+
+        let db = JiraDatabase::open(Backend::Sqlite, sqlite_path).unwrap();
+        db.undo().unwrap();
+
+        assert!(db.read_db().unwrap().epics.is_empty());
+    }
+
+    fn sample_state() -> DBState {
+        let story = Story {
+            name: "story 1 name".to_string(),
+            description: "story 1 description".to_string(),
+            status: Status::new("in_progress"),
+            priority: Priority::Highest,
+            assignee: Some("jdoe".to_string()),
+            reporter: None,
+            labels: vec!["backend".to_string(), "api".to_string()],
+            due_date: NaiveDate::from_ymd_opt(2024, 1, 31),
+            estimate: Some(8),
+            subtasks: vec![
+                Subtask {
+                    name: "write migration".to_string(),
+                    done: true,
+                },
+                Subtask::new("update docs".to_string()),
+            ],
+            created_at: Some(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()),
+            updated_at: Some(Utc.with_ymd_and_hms(2024, 1, 2, 8, 30, 0).unwrap()),
+        };
+        let epic = Epic {
+            name: "epic 1 name".to_string(),
+            description: "epic 1 description".to_string(),
+            status: Status::new("open"),
+            priority: Priority::Low,
+            stories: vec![2],
+            assignee: None,
+            reporter: Some("jdoe".to_string()),
+            labels: vec!["backend".to_string()],
+            due_date: None,
+            created_at: None,
+            updated_at: None,
+        };
+        let user = User {
+            name: "Jane Doe".to_string(),
+        };
+
+        DBState {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            last_item_id: 2,
+            epics: HashMap::from([(1, epic)]),
+            stories: HashMap::from([(2, story)]),
+            users: HashMap::from([("jdoe".to_string(), user)]),
+            labels: BTreeMap::from([
+                ("api".to_string(), vec![2]),
+                ("backend".to_string(), vec![1, 2]),
+            ]),
+            comments: HashMap::from([(
+                2,
+                vec![Comment {
+                    id: 1,
+                    author: "jdoe".to_string(),
+                    created_at: Utc.with_ymd_and_hms(2024, 1, 3, 9, 0, 0).unwrap(),
+                    body: "looks good".to_string(),
+                    edited_at: None,
+                }],
+            )]),
+            links: HashMap::from([(
+                2,
+                vec![Link {
+                    kind: LinkKind::Blocks,
+                    to: 1,
+                }],
+            )]),
+        }
+    }
+}
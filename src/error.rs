@@ -0,0 +1,73 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JiraError {
+    #[error("could not find epic {0} in database!")]
+    EpicNotFound(u32),
+    #[error("could not find story {0} in database!")]
+    StoryNotFound(u32),
+    #[error("story {story_id} is not part of epic {epic_id}")]
+    StoryNotInEpic { epic_id: u32, story_id: u32 },
+    #[error("nothing to undo")]
+    NothingToUndo,
+    #[error("nothing to redo")]
+    NothingToRedo,
+    #[error("could not access database: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("database {0} is locked by another process, try again later")]
+    Locked(String),
+    #[error("could not access sqlite database: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("database is corrupt: {0}")]
+    Corrupt(String),
+    #[error(
+        "database uses schema version {found}, but this build only supports up to {supported}"
+    )]
+    UnsupportedSchemaVersion { found: u32, supported: u32 },
+    #[error("database is out of sync: {0}")]
+    Conflict(String),
+    #[error("could not find backup {0}")]
+    BackupNotFound(String),
+    #[error("there is no board at {0}")]
+    NoBoard(String),
+    #[error("there is already a board at {0}")]
+    BoardExists(String),
+    #[error("invalid config {0}: {1}")]
+    InvalidConfig(String, String),
+    #[error("the workflow does not allow moving from {from} to {to}")]
+    IllegalTransition { from: String, to: String },
+    #[error("epic {epic_id} still has {open} unfinished stories")]
+    UnfinishedStories { epic_id: u32, open: usize },
+    #[error("could not find user {0}, add them with `users add`")]
+    UserNotFound(String),
+    #[error("there is already a user {0}")]
+    UserExists(String),
+    #[error("invalid label \"{0}\", labels may not be empty or contain spaces or commas")]
+    InvalidLabel(String),
+    #[error("{estimate} is not on the estimate scale ({scale})")]
+    InvalidEstimate { estimate: u32, scale: String },
+    #[error("could not find comment {comment_id} on story {story_id}")]
+    CommentNotFound { story_id: u32, comment_id: u32 },
+    #[error("comment {0} was written by someone else")]
+    NotCommentAuthor(u32),
+    #[error("comments cannot be empty")]
+    EmptyComment,
+    #[error("story {story_id} has no subtask {}", .index + 1)]
+    SubtaskNotFound { story_id: u32, index: usize },
+    #[error("subtasks need a name")]
+    EmptySubtask,
+    #[error("could not find an epic or story with id {0}")]
+    ItemNotFound(u32),
+    #[error("{0} cannot be linked to itself")]
+    SelfLink(u32),
+    #[error("blocks links may not go in a circle: {0}")]
+    BlockCycle(String),
+}
+
+impl From<serde_json::Error> for JiraError {
+    fn from(error: serde_json::Error) -> Self {
+        JiraError::Corrupt(error.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, JiraError>;
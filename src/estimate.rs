@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{Epic, Story},
+    workflow::{Category, Workflow},
+};
+
+// the story points a story may be estimated with, smallest first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EstimateScale(Vec<u32>);
+
+impl Default for EstimateScale {
+    fn default() -> Self {
+        Self(vec![1, 2, 3, 5, 8, 13, 21])
+    }
+}
+
+impl EstimateScale {
+    pub fn points(&self) -> &[u32] {
+        &self.0
+    }
+
+    pub fn allows(&self, estimate: u32) -> bool {
+        self.0.contains(&estimate)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.0.is_empty() {
+            return Err("estimate_scale has no points".to_string());
+        }
+        if !self.0.iter().tuple_windows().all(|(a, b)| a < b) {
+            return Err("estimate_scale must be in increasing order".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for EstimateScale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().join(", "))
+    }
+}
+
+// summed in u64 so a board with a large scale cannot overflow its totals
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Points {
+    pub total: u64,
+    // the points of stories in a `done` state
+    pub completed: u64,
+}
+
+impl Points {
+    // what an epic's stories add up to. stories without an estimate count as 0
+    pub fn of_epic(epic: &Epic, stories: &HashMap<u32, Story>, workflow: &Workflow) -> Self {
+        epic.stories
+            .iter()
+            .filter_map(|story_id| stories.get(story_id))
+            .fold(Points::default(), |points, story| {
+                let estimate = u64::from(story.estimate.unwrap_or(0));
+                let done = workflow.category_of(&story.status) == Some(Category::Done);
+
+                Points {
+                    total: points.total.saturating_add(estimate),
+                    completed: points
+                        .completed
+                        .saturating_add(if done { estimate } else { 0 }),
+                }
+            })
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.total - self.completed
+    }
+
+    // nothing estimated yet has no percentage
+    pub fn percent_done(&self) -> Option<u32> {
+        (self.total > 0).then(|| (u128::from(self.completed) * 100 / u128::from(self.total)) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Status;
+
+    #[test]
+    fn validate_should_require_increasing_points() {
+        assert!(EstimateScale::default().validate().is_ok());
+        assert!(EstimateScale(vec![]).validate().is_err());
+        assert!(EstimateScale(vec![1, 3, 2]).validate().is_err());
+        assert!(EstimateScale(vec![1, 1]).validate().is_err());
+    }
+
+    #[test]
+    fn of_epic_should_add_up_estimates_of_known_stories() {
+        let story = |estimate, status: &str| {
+            let mut story = Story::new("".to_string(), "".to_string());
+            story.estimate = estimate;
+            story.status = Status::new(status);
+            story
+        };
+        let mut epic = Epic::new("".to_string(), "".to_string());
+        epic.stories = vec![2, 3, 4, 99];
+        let stories = HashMap::from([
+            (2, story(Some(5), "closed")),
+            (3, story(Some(8), "in_progress")),
+            (4, story(None, "open")),
+        ]);
+
+        let points = Points::of_epic(&epic, &stories, &Workflow::default());
+
+        assert_eq!(
+            points,
+            Points {
+                total: 13,
+                completed: 5
+            }
+        );
+        assert_eq!(points.remaining(), 8);
+        assert_eq!(points.percent_done(), Some(38));
+        assert_eq!(Points::default().percent_done(), None);
+    }
+
+    #[test]
+    fn of_epic_should_not_overflow_on_large_estimates() {
+        let mut story = Story::new("".to_string(), "".to_string());
+        story.estimate = Some(u32::MAX);
+        story.status = Status::new("closed");
+        let mut epic = Epic::new("".to_string(), "".to_string());
+        epic.stories = vec![2, 3];
+        let stories = HashMap::from([(2, story.clone()), (3, story)]);
+
+        let points = Points::of_epic(&epic, &stories, &Workflow::default());
+
+        assert_eq!(points.total, 2 * u64::from(u32::MAX));
+        assert_eq!(points.percent_done(), Some(100));
+    }
+}
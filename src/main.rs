@@ -1,18 +1,93 @@
-use std::rc::Rc;
+use std::{process, rc::Rc};
 
-use db::JiraDatabase;
+use clap::Parser;
+
+use cli::{Cli, Command};
 use io_utils::{get_user_input, wait_for_key_press};
 use navigator::Navigator;
 
+mod cli;
+mod clock;
 mod db;
+mod error;
+mod estimate;
 mod io_utils;
 mod models;
 mod navigator;
+mod project;
 mod ui;
+mod workflow;
 
 fn main() {
-    let db = Rc::new(JiraDatabase::new("data/db.json".to_string()));
+    let cli = Cli::parse();
+
+    if let Some(Command::Init {
+        with_config,
+        sample,
+        force,
+    }) = &cli.command
+    {
+        if let Err(error) = cli::run_init(&cli, *with_config, *sample, *force) {
+            println!("Error: {:#}", error);
+            process::exit(1);
+        }
+        return;
+    }
+    let location = match cli.location() {
+        Ok(location) => location,
+        Err(error) => {
+            println!("Error opening database: {}", error);
+            process::exit(1);
+        }
+    };
+
+    match &cli.command {
+        Some(Command::Backups { command }) => {
+            if let Err(error) = cli::run_backups(&cli, &location, command) {
+                println!("Error: {:#}", error);
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Users { command }) => {
+            if let Err(error) = cli::run_users(&cli, &location, command) {
+                println!("Error: {:#}", error);
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Fsck { repair }) => {
+            match cli::run_fsck(&cli, &location, *repair) {
+                Ok(true) => {}
+                Ok(false) => process::exit(1),
+                Err(error) => {
+                    println!("Error: {:#}", error);
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Init { .. }) | None => {}
+    }
+
+    match cli::offer_to_create(&location) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(error) => {
+            println!("Error: {:#}", error);
+            process::exit(1);
+        }
+    }
+
+    let db = match cli.open_database(&location) {
+        Ok(db) => Rc::new(db),
+        Err(error) => {
+            println!("Error opening database: {}", error);
+            return;
+        }
+    };
     let mut navigator = Navigator::new(Rc::clone(&db));
+    navigator.set_user(cli.user.clone());
 
     loop {
         clearscreen::clear().unwrap();
@@ -1,5 +1,9 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
 
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, PartialEq, Eq)]
@@ -8,29 +12,95 @@ pub enum Action {
     NavigateToStoryDetail { epic_id: u32, story_id: u32 },
     NavigateToPreviousPage,
     CreateEpic,
+    UpdateEpic { epic_id: u32 },
     UpdateEpicStatus { epic_id: u32 },
     DeleteEpic { epic_id: u32 },
     CreateStory { epic_id: u32 },
+    UpdateStory { story_id: u32 },
     UpdateStoryStatus { story_id: u32 },
     DeleteStory { epic_id: u32, story_id: u32 },
+    MoveStory { epic_id: u32, story_id: u32 },
+    AssignEpic { epic_id: u32 },
+    AssignStory { story_id: u32 },
+    AddComment { story_id: u32 },
+    EditComment { story_id: u32 },
+    DeleteComment { story_id: u32 },
+    EstimateStory { story_id: u32 },
+    AddSubtask { story_id: u32 },
+    ToggleSubtask { story_id: u32 },
+    RemoveSubtask { story_id: u32 },
+    MoveSubtask { story_id: u32 },
+    LinkEpic { epic_id: u32 },
+    UnlinkEpic { epic_id: u32 },
+    LinkStory { story_id: u32 },
+    UnlinkStory { story_id: u32 },
+    AddEpicLabel { epic_id: u32 },
+    RemoveEpicLabel { epic_id: u32 },
+    AddStoryLabel { story_id: u32 },
+    RemoveStoryLabel { story_id: u32 },
+    FilterByLabel,
+    NavigateToMyWork,
+    NavigateToDueDates,
+    Undo,
+    Redo,
     Exit,
 }
 
-#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
-pub enum Status {
-    Open,
-    InProgress,
-    Resolved,
-    Closed,
+// id of a state in the board's workflow, see `workflow::Workflow`
+#[derive(PartialEq, Eq, Hash, Debug, Serialize, Deserialize, Clone, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Status(pub String);
+
+impl Status {
+    pub fn new(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+// where new items start in the default workflow. `JiraDatabase` moves them to the initial state
+// of the board's own workflow when they are created
+impl Default for Status {
+    fn default() -> Self {
+        Self::new("open")
+    }
 }
 
 impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// declared from most to least urgent, so sorting puts the most urgent first
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone, Copy, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Highest,
+    High,
+    #[default]
+    Medium,
+    Low,
+    Lowest,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 5] = [
+        Priority::Highest,
+        Priority::High,
+        Priority::Medium,
+        Priority::Low,
+        Priority::Lowest,
+    ];
+}
+
+impl Display for Priority {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Status::Open => write!(f, "OPEN"),
-            Status::InProgress => write!(f, "IN PROGRESS"),
-            Status::Resolved => write!(f, "RESOLVED"),
-            Status::Closed => write!(f, "CLOSED"),
+            Priority::Highest => write!(f, "HIGHEST"),
+            Priority::High => write!(f, "HIGH"),
+            Priority::Medium => write!(f, "MEDIUM"),
+            Priority::Low => write!(f, "LOW"),
+            Priority::Lowest => write!(f, "LOWEST"),
         }
     }
 }
@@ -40,7 +110,22 @@ pub struct Epic {
     pub name: String,
     pub description: String,
     pub status: Status,
+    pub priority: Priority,
     pub stories: Vec<u32>,
+    // user ids from `DBState::users`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reporter: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<NaiveDate>,
+    // set by `JiraDatabase`, items written before they existed have neither
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl Epic {
@@ -48,8 +133,15 @@ impl Epic {
         Self {
             name,
             description,
-            status: Status::Open,
+            status: Status::default(),
+            priority: Priority::default(),
             stories: vec![],
+            assignee: None,
+            reporter: None,
+            labels: vec![],
+            due_date: None,
+            created_at: None,
+            updated_at: None,
         }
     }
 }
@@ -59,6 +151,27 @@ pub struct Story {
     pub name: String,
     pub description: String,
     pub status: Status,
+    pub priority: Priority,
+    // user ids from `DBState::users`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reporter: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<NaiveDate>,
+    // story points, one of the board's `EstimateScale`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<u32>,
+    // a checklist, in the order it is worked through
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtasks: Vec<Subtask>,
+    // set by `JiraDatabase`, items written before they existed have neither
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl Story {
@@ -66,14 +179,106 @@ impl Story {
         Self {
             name,
             description,
-            status: Status::Open,
+            status: Status::default(),
+            priority: Priority::default(),
+            assignee: None,
+            reporter: None,
+            labels: vec![],
+            due_date: None,
+            estimate: None,
+            subtasks: vec![],
+            created_at: None,
+            updated_at: None,
         }
     }
 }
 
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
+pub struct Subtask {
+    pub name: String,
+    pub done: bool,
+}
+
+impl Subtask {
+    pub fn new(name: String) -> Self {
+        Self { name, done: false }
+    }
+}
+
+// "is blocked by" and "is duplicated by" are the same links read from the other end
+#[derive(PartialEq, Eq, Hash, Debug, Serialize, Deserialize, Clone, Copy, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Blocks,
+    RelatesTo,
+    Duplicates,
+}
+
+impl LinkKind {
+    // how the item a link goes out of refers to the other one
+    pub fn outbound(&self) -> &'static str {
+        match self {
+            LinkKind::Blocks => "blocks",
+            LinkKind::RelatesTo => "relates to",
+            LinkKind::Duplicates => "duplicates",
+        }
+    }
+
+    // how the item a link points to refers to the other one
+    pub fn inbound(&self) -> &'static str {
+        match self {
+            LinkKind::Blocks => "is blocked by",
+            LinkKind::RelatesTo => "relates to",
+            LinkKind::Duplicates => "is duplicated by",
+        }
+    }
+}
+
+// a link to another epic or story
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct Link {
+    pub kind: LinkKind,
+    pub to: u32,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
+pub struct User {
+    pub name: String,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
+pub struct Comment {
+    // unique among the comments of one story
+    pub id: u32,
+    // a user id from `DBState::users`
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_at: Option<DateTime<Utc>>,
+}
+
 #[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
 pub struct DBState {
+    pub schema_version: u32,
     pub last_item_id: u32,
     pub epics: HashMap<u32, Epic>,
     pub stories: HashMap<u32, Story>,
+    // keyed by the short id items refer to people by, e.g. "jdoe"
+    pub users: HashMap<String, User>,
+    // label -> ids of the epics and stories carrying it, in ascending order. `JiraDatabase` keeps
+    // it in step with the items so listing labels never has to scan the whole board
+    pub labels: BTreeMap<String, Vec<u32>>,
+    // story id -> its comments, oldest first
+    pub comments: HashMap<u32, Vec<Comment>>,
+    // epic or story id -> the links going out of it, oldest first
+    pub links: HashMap<u32, Vec<Link>>,
+}
+
+// snapshots of DBState taken before each change, newest last
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct History {
+    pub schema_version: u32,
+    pub undo: Vec<DBState>,
+    pub redo: Vec<DBState>,
 }
@@ -1,37 +1,105 @@
-use std::rc::Rc;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
-use anyhow::{anyhow, Context, Ok, Result};
+use anyhow::{anyhow, bail, Context, Ok, Result};
 
 use crate::{
-    db::JiraDatabase,
-    models::Action,
-    ui::{EpicDetail, HomePage, Page, Prompts, StoryDetail},
+    db::{links, JiraDatabase},
+    error::JiraError,
+    models::{Action, Comment, Subtask},
+    ui::{DueDates, EpicDetail, HomePage, MyWork, Page, Prompts, StoryDetail},
+    workflow::Category,
 };
 
 pub struct Navigator {
     pages: Vec<Box<dyn Page>>,
     propmpts: Prompts,
     db: Rc<JiraDatabase>,
+    // who is using the board, see `--user`
+    user: Option<String>,
 }
 
 impl Navigator {
     pub fn new(db: Rc<JiraDatabase>) -> Self {
         Self {
-            pages: vec![Box::new(HomePage { db: Rc::clone(&db) })],
+            pages: vec![Box::new(HomePage {
+                db: Rc::clone(&db),
+                sort: Cell::default(),
+                filter: RefCell::default(),
+            })],
             propmpts: Prompts::new(),
             db,
+            user: None,
+        }
+    }
+
+    pub fn set_user(&mut self, user: Option<String>) {
+        self.user = user;
+    }
+
+    fn current_user(&self) -> Result<String> {
+        match &self.user {
+            Some(user) => Ok(user.clone()),
+            None => bail!("no current user, set one with --user or JIRA_USER"),
         }
     }
 
-    pub fn get_current_page(&self) -> Option<&Box<dyn Page>> {
-        self.pages.last()
+    // new items are reported by the current user, once they are in the registry
+    fn reporter(&self) -> Result<Option<String>> {
+        let users = self.db.read_db()?.users;
+        Ok(self.user.clone().filter(|user| users.contains_key(user)))
+    }
+
+    pub fn get_current_page(&self) -> Option<&dyn Page> {
+        self.pages.last().map(|page| page.as_ref())
     }
 
     pub fn handle_action(&mut self, action: Action) -> Result<()> {
+        let result = self.dispatch_action(action);
+
+        if let Err(error) = &result {
+            // the epic or story on screen was removed underneath us, so its page is stale
+            if let Some(error) = error.downcast_ref::<JiraError>() {
+                if self.current_page_shows_missing(error) {
+                    self.pages.pop();
+                }
+            }
+        }
+
+        result
+    }
+
+    // a missing id only makes the page stale if it is the one the page is showing
+    fn current_page_shows_missing(&self, error: &JiraError) -> bool {
+        let page = match self.pages.last() {
+            Some(page) => page.as_any(),
+            None => return false,
+        };
+
+        match error {
+            JiraError::EpicNotFound(epic_id) => {
+                page.downcast_ref::<EpicDetail>()
+                    .is_some_and(|page| page.epic_id == *epic_id)
+                    || page
+                        .downcast_ref::<StoryDetail>()
+                        .is_some_and(|page| page.epic_id == *epic_id)
+            }
+            JiraError::StoryNotFound(story_id) => page
+                .downcast_ref::<StoryDetail>()
+                .is_some_and(|page| page.story_id == *story_id),
+            _ => false,
+        }
+    }
+
+    fn dispatch_action(&mut self, action: Action) -> Result<()> {
         match action {
             Action::NavigateToEpicDetail { epic_id } => self.pages.push(Box::new(EpicDetail {
                 epic_id,
                 db: Rc::clone(&self.db),
+                sort: Cell::default(),
+                filter: RefCell::default(),
             })),
             Action::NavigateToStoryDetail { epic_id, story_id } => {
                 self.pages.push(Box::new(StoryDetail {
@@ -45,18 +113,91 @@ impl Navigator {
                     self.pages.pop();
                 }
             }
+            Action::NavigateToMyWork => {
+                let user = self.current_user()?;
+                self.pages.push(Box::new(MyWork {
+                    user,
+                    db: Rc::clone(&self.db),
+                }))
+            }
+            Action::NavigateToDueDates => self.pages.push(Box::new(DueDates {
+                db: Rc::clone(&self.db),
+            })),
             Action::CreateEpic => {
-                let epic = (self.propmpts.create_epic)();
+                let mut epic = (self.propmpts.create_epic)();
+                epic.reporter = self.reporter()?;
                 self.db
                     .create_epic(epic)
                     .with_context(|| anyhow!("failed to create epic!"))?;
             }
+            Action::UpdateEpic { epic_id } => {
+                let db_state = self.db.read_db()?;
+                let epic = db_state
+                    .epics
+                    .get(&epic_id)
+                    .ok_or(JiraError::EpicNotFound(epic_id))?;
+                let edited = (self.propmpts.update_epic)(epic);
+                self.db
+                    .update_epic(
+                        epic_id,
+                        edited.name,
+                        edited.description,
+                        edited.priority,
+                        edited.due_date,
+                    )
+                    .with_context(|| anyhow!("failed to update epic!"))?;
+            }
             Action::UpdateEpicStatus { epic_id } => {
-                (self.propmpts.update_status)().map(|status| {
+                let db_state = self.db.read_db()?;
+                let epic = db_state
+                    .epics
+                    .get(&epic_id)
+                    .ok_or(JiraError::EpicNotFound(epic_id))?;
+                let workflow = &self.db.workflows().epic;
+                let targets = workflow.targets(&epic.status);
+                if targets.is_empty() {
+                    bail!("{} is a final state", workflow.name_of(&epic.status));
+                }
+
+                if let Some(status) = (self.propmpts.update_status)(&targets) {
                     self.db
                         .update_epic_status(epic_id, status)
-                        .with_context(|| anyhow!("failed to update epic!"))
-                });
+                        .with_context(|| anyhow!("failed to update epic!"))?;
+                }
+            }
+            Action::AssignEpic { epic_id } => {
+                let db_state = self.db.read_db()?;
+                let epic = db_state
+                    .epics
+                    .get(&epic_id)
+                    .ok_or(JiraError::EpicNotFound(epic_id))?;
+                let edited = (self.propmpts.assign_epic)(epic);
+                self.db
+                    .assign_epic(epic_id, edited.assignee, edited.reporter)
+                    .with_context(|| anyhow!("failed to assign epic!"))?;
+            }
+            Action::AddEpicLabel { epic_id } => {
+                if let Some(label) = (self.propmpts.add_label)() {
+                    self.db
+                        .add_epic_label(epic_id, &label)
+                        .with_context(|| anyhow!("failed to label epic!"))?;
+                }
+            }
+            Action::RemoveEpicLabel { epic_id } => {
+                let db_state = self.db.read_db()?;
+                let epic = db_state
+                    .epics
+                    .get(&epic_id)
+                    .ok_or(JiraError::EpicNotFound(epic_id))?;
+                if epic.labels.is_empty() {
+                    bail!("epic {epic_id} has no labels");
+                }
+
+                if let Some(label) = (self.propmpts.remove_label)(&epic.labels) {
+                    self.db
+                        .remove_epic_label(epic_id, &label)
+                        .with_context(|| anyhow!("failed to remove label!"))?;
+                }
             }
             Action::DeleteEpic { epic_id } => {
                 if (self.propmpts.delete_epic)() {
@@ -70,17 +211,181 @@ impl Navigator {
                 }
             }
             Action::CreateStory { epic_id } => {
-                let story = (self.propmpts.create_story)();
+                let mut story = (self.propmpts.create_story)();
+                story.reporter = self.reporter()?;
                 self.db
                     .create_story(story, epic_id)
                     .with_context(|| anyhow!("failed to create story!"))?;
             }
+            Action::UpdateStory { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state
+                    .stories
+                    .get(&story_id)
+                    .ok_or(JiraError::StoryNotFound(story_id))?;
+                let edited = (self.propmpts.update_story)(story);
+                self.db
+                    .update_story(
+                        story_id,
+                        edited.name,
+                        edited.description,
+                        edited.priority,
+                        edited.due_date,
+                    )
+                    .with_context(|| anyhow!("failed to update story!"))?;
+            }
             Action::UpdateStoryStatus { story_id } => {
-                (self.propmpts.update_status)().map(|status| {
+                let db_state = self.db.read_db()?;
+                let story = db_state
+                    .stories
+                    .get(&story_id)
+                    .ok_or(JiraError::StoryNotFound(story_id))?;
+                let workflow = &self.db.workflows().story;
+                let targets = workflow.targets(&story.status);
+                if targets.is_empty() {
+                    bail!("{} is a final state", workflow.name_of(&story.status));
+                }
+
+                if let Some(status) = (self.propmpts.update_status)(&targets) {
+                    let resolving = workflow.category_of(&status) == Some(Category::Done);
+                    let blockers = if resolving {
+                        self.db.open_blockers(story_id)?
+                    } else {
+                        vec![]
+                    };
+
+                    if blockers.is_empty() || (self.propmpts.resolve_blocked)(&blockers) {
+                        self.db
+                            .update_story_status(story_id, status)
+                            .with_context(|| anyhow!("failed to update story!"))?;
+                    }
+                }
+            }
+            Action::AssignStory { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state
+                    .stories
+                    .get(&story_id)
+                    .ok_or(JiraError::StoryNotFound(story_id))?;
+                let edited = (self.propmpts.assign_story)(story);
+                self.db
+                    .assign_story(story_id, edited.assignee, edited.reporter)
+                    .with_context(|| anyhow!("failed to assign story!"))?;
+            }
+            Action::AddComment { story_id } => {
+                let author = self.current_user()?;
+                if let Some(body) = (self.propmpts.add_comment)() {
+                    self.db
+                        .add_comment(story_id, author, body)
+                        .with_context(|| anyhow!("failed to add comment!"))?;
+                }
+            }
+            Action::EditComment { story_id } => {
+                let author = self.current_user()?;
+                let own = self.own_comments(story_id, &author)?;
+
+                if let Some((comment_id, body)) = (self.propmpts.edit_comment)(&own) {
+                    self.db
+                        .edit_comment(story_id, comment_id, &author, body)
+                        .with_context(|| anyhow!("failed to edit comment!"))?;
+                }
+            }
+            Action::DeleteComment { story_id } => {
+                let author = self.current_user()?;
+                let own = self.own_comments(story_id, &author)?;
+
+                if let Some(comment_id) = (self.propmpts.delete_comment)(&own) {
+                    self.db
+                        .delete_comment(story_id, comment_id, &author)
+                        .with_context(|| anyhow!("failed to delete comment!"))?;
+                }
+            }
+            Action::AddSubtask { story_id } => {
+                if let Some(name) = (self.propmpts.add_subtask)() {
+                    self.db
+                        .add_subtask(story_id, &name)
+                        .with_context(|| anyhow!("failed to add subtask!"))?;
+                }
+            }
+            Action::ToggleSubtask { story_id } => {
+                let subtasks = self.subtasks(story_id)?;
+
+                if let Some(index) = (self.propmpts.toggle_subtask)(&subtasks) {
+                    self.db
+                        .toggle_subtask(story_id, index)
+                        .with_context(|| anyhow!("failed to update subtask!"))?;
+                }
+            }
+            Action::RemoveSubtask { story_id } => {
+                let subtasks = self.subtasks(story_id)?;
+
+                if let Some(index) = (self.propmpts.remove_subtask)(&subtasks) {
+                    self.db
+                        .remove_subtask(story_id, index)
+                        .with_context(|| anyhow!("failed to remove subtask!"))?;
+                }
+            }
+            Action::MoveSubtask { story_id } => {
+                let subtasks = self.subtasks(story_id)?;
+
+                if let Some((from, to)) = (self.propmpts.move_subtask)(&subtasks) {
+                    self.db
+                        .move_subtask(story_id, from, to)
+                        .with_context(|| anyhow!("failed to move subtask!"))?;
+                }
+            }
+            Action::LinkEpic { epic_id } => self.link_item(epic_id, "epic")?,
+            Action::UnlinkEpic { epic_id } => self.unlink_item(epic_id, "epic")?,
+            Action::LinkStory { story_id } => self.link_item(story_id, "story")?,
+            Action::UnlinkStory { story_id } => self.unlink_item(story_id, "story")?,
+            Action::EstimateStory { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state
+                    .stories
+                    .get(&story_id)
+                    .ok_or(JiraError::StoryNotFound(story_id))?;
+                let estimate = (self.propmpts.estimate_story)(
+                    self.db.estimate_scale().points(),
+                    story.estimate,
+                );
+                self.db
+                    .estimate_story(story_id, estimate)
+                    .with_context(|| anyhow!("failed to estimate story!"))?;
+            }
+            Action::AddStoryLabel { story_id } => {
+                if let Some(label) = (self.propmpts.add_label)() {
                     self.db
-                        .update_story_status(story_id, status)
-                        .with_context(|| anyhow!("failed to update story!"))
-                });
+                        .add_story_label(story_id, &label)
+                        .with_context(|| anyhow!("failed to label story!"))?;
+                }
+            }
+            Action::RemoveStoryLabel { story_id } => {
+                let db_state = self.db.read_db()?;
+                let story = db_state
+                    .stories
+                    .get(&story_id)
+                    .ok_or(JiraError::StoryNotFound(story_id))?;
+                if story.labels.is_empty() {
+                    bail!("story {story_id} has no labels");
+                }
+
+                if let Some(label) = (self.propmpts.remove_label)(&story.labels) {
+                    self.db
+                        .remove_story_label(story_id, &label)
+                        .with_context(|| anyhow!("failed to remove label!"))?;
+                }
+            }
+            Action::FilterByLabel => {
+                let filter = match self.current_filter() {
+                    Some(filter) => filter,
+                    None => return Ok(()),
+                };
+                let counts = self.db.label_counts()?;
+                if counts.is_empty() && filter.borrow().is_none() {
+                    bail!("nothing is labelled yet");
+                }
+
+                *filter.borrow_mut() = (self.propmpts.filter_label)(&counts);
             }
             Action::DeleteStory { epic_id, story_id } => {
                 if (self.propmpts.delete_story)() {
@@ -93,12 +398,172 @@ impl Navigator {
                     }
                 }
             }
+            Action::MoveStory { epic_id, story_id } => {
+                if let Some(to_epic_id) = (self.propmpts.move_story)() {
+                    self.db
+                        .move_story(story_id, epic_id, to_epic_id)
+                        .with_context(|| anyhow!("failed to move story!"))?;
+
+                    // the pages follow the story into its new epic
+                    self.pages.pop();
+                    let below_is_old_epic = self.pages.last().is_some_and(|page| {
+                        page.as_any()
+                            .downcast_ref::<EpicDetail>()
+                            .is_some_and(|page| page.epic_id == epic_id)
+                    });
+                    if below_is_old_epic {
+                        self.pages.pop();
+                        self.pages.push(Box::new(EpicDetail {
+                            epic_id: to_epic_id,
+                            db: Rc::clone(&self.db),
+                            sort: Cell::default(),
+                            filter: RefCell::default(),
+                        }));
+                    }
+                    self.pages.push(Box::new(StoryDetail {
+                        epic_id: to_epic_id,
+                        story_id,
+                        db: Rc::clone(&self.db),
+                    }));
+                }
+            }
+            Action::Undo => {
+                self.db.undo().with_context(|| anyhow!("failed to undo!"))?;
+                self.drop_missing_pages()?;
+            }
+            Action::Redo => {
+                self.db.redo().with_context(|| anyhow!("failed to redo!"))?;
+                self.drop_missing_pages()?;
+            }
             Action::Exit => self.pages.clear(),
         }
 
         Ok(())
     }
 
+    fn link_item(&self, id: u32, what: &str) -> Result<()> {
+        if let Some((from, kind, to)) = (self.propmpts.link_item)(id) {
+            self.db
+                .link(from, kind, to)
+                .with_context(|| anyhow!("failed to link {what}!"))?;
+        }
+
+        Ok(())
+    }
+
+    // offers the links going out of the item first, then the ones pointing at it
+    fn unlink_item(&self, id: u32, what: &str) -> Result<()> {
+        let db_state = self.db.read_db()?;
+        let outbound = db_state
+            .links
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(|link| (id, link.kind, link.to));
+        let inbound = links::inbound(&db_state.links, id)
+            .into_iter()
+            .map(|(from, kind)| (from, kind, id));
+        let links: Vec<_> = outbound.chain(inbound).collect();
+        if links.is_empty() {
+            bail!("{what} {id} has no links");
+        }
+
+        if let Some(index) = (self.propmpts.unlink_item)(&links) {
+            let (from, kind, to) = links[index];
+            self.db
+                .unlink(from, kind, to)
+                .with_context(|| anyhow!("failed to unlink {what}!"))?;
+        }
+
+        Ok(())
+    }
+
+    fn subtasks(&self, story_id: u32) -> Result<Vec<Subtask>> {
+        let subtasks = self
+            .db
+            .read_db()?
+            .stories
+            .remove(&story_id)
+            .ok_or(JiraError::StoryNotFound(story_id))?
+            .subtasks;
+        if subtasks.is_empty() {
+            bail!("story {story_id} has no subtasks");
+        }
+
+        Ok(subtasks)
+    }
+
+    // only the author may edit or delete a comment, so nobody else's are offered
+    fn own_comments(&self, story_id: u32, author: &str) -> Result<Vec<Comment>> {
+        let own: Vec<Comment> = self
+            .db
+            .read_db()?
+            .comments
+            .remove(&story_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|comment| comment.author == author)
+            .collect();
+        if own.is_empty() {
+            bail!("you have no comments on story {story_id}");
+        }
+
+        Ok(own)
+    }
+
+    // the label filter of the page on screen, if it has one
+    fn current_filter(&self) -> Option<&RefCell<Option<String>>> {
+        let page = self.pages.last()?.as_any();
+
+        if let Some(home_page) = page.downcast_ref::<HomePage>() {
+            Some(&home_page.filter)
+        } else {
+            page.downcast_ref::<EpicDetail>()
+                .map(|epic_detail| &epic_detail.filter)
+        }
+    }
+
+    // undo and redo can remove the epic or story on screen, so leave its pages,
+    // or move the story to another epic, so point its page at the new owner
+    fn drop_missing_pages(&mut self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+
+        let pages = std::mem::take(&mut self.pages);
+        for page in pages {
+            let any = page.as_any();
+            if let Some(epic_detail) = any.downcast_ref::<EpicDetail>() {
+                if !db_state.epics.contains_key(&epic_detail.epic_id) {
+                    continue;
+                }
+            } else if let Some(story_detail) = any.downcast_ref::<StoryDetail>() {
+                let story_id = story_detail.story_id;
+                if !db_state.stories.contains_key(&story_id) {
+                    continue;
+                }
+                let owner = db_state
+                    .epics
+                    .iter()
+                    .find(|(_, epic)| epic.stories.contains(&story_id))
+                    .map(|(epic_id, _)| *epic_id);
+                match owner {
+                    Some(epic_id) if epic_id == story_detail.epic_id => {}
+                    Some(epic_id) => {
+                        self.pages.push(Box::new(StoryDetail {
+                            epic_id,
+                            story_id,
+                            db: Rc::clone(&self.db),
+                        }));
+                        continue;
+                    }
+                    None => continue,
+                }
+            }
+            self.pages.push(page);
+        }
+
+        Ok(())
+    }
+
     // fn for testing
     #[allow(dead_code)]
     fn get_page_count(&self) -> usize {
@@ -114,17 +579,16 @@ impl Navigator {
 #[cfg(test)]
 mod tests {
     use crate::{
-        db::test_utils::MockDB,
-        models::{Epic, Status, Story},
+        db::test_utils::{FailingDB, MockDB},
+        models::{Epic, LinkKind, Status, Story},
+        workflow::Workflows,
     };
 
     use super::*;
 
     #[test]
     fn should_start_on_home_page() {
-        let nav = Navigator::new(Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        }));
+        let nav = Navigator::new(Rc::new(JiraDatabase::new(Box::new(MockDB::new()))));
         assert_eq!(nav.get_page_count(), 1);
 
         let home_page = nav
@@ -132,14 +596,12 @@ mod tests {
             .unwrap()
             .as_any()
             .downcast_ref::<HomePage>();
-        assert_eq!(home_page.is_some(), true);
+        assert!(home_page.is_some());
     }
 
     #[test]
     fn handle_action_should_navigate_pages() {
-        let mut nav = Navigator::new(Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        }));
+        let mut nav = Navigator::new(Rc::new(JiraDatabase::new(Box::new(MockDB::new()))));
 
         nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 })
             .unwrap();
@@ -150,7 +612,7 @@ mod tests {
             .unwrap()
             .as_any()
             .downcast_ref::<EpicDetail>();
-        assert_eq!(epic_detail_page.is_some(), true);
+        assert!(epic_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToStoryDetail {
             epic_id: 1,
@@ -164,7 +626,7 @@ mod tests {
             .unwrap()
             .as_any()
             .downcast_ref::<StoryDetail>();
-        assert_eq!(story_detail_page.is_some(), true);
+        assert!(story_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 2);
@@ -174,7 +636,7 @@ mod tests {
             .unwrap()
             .as_any()
             .downcast_ref::<EpicDetail>();
-        assert_eq!(epic_detail_page.is_some(), true);
+        assert!(epic_detail_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 1);
@@ -184,7 +646,7 @@ mod tests {
             .unwrap()
             .as_any()
             .downcast_ref::<HomePage>();
-        assert_eq!(home_page.is_some(), true);
+        assert!(home_page.is_some());
 
         nav.handle_action(Action::NavigateToPreviousPage).unwrap();
         assert_eq!(nav.get_page_count(), 0);
@@ -195,9 +657,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_create_epic() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
         let mut nav = Navigator::new(Rc::clone(&db));
         let mut prompts = Prompts::new();
 
@@ -214,32 +674,483 @@ mod tests {
     }
 
     #[test]
-    fn handle_action_should_handle_update_epic() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
+    fn handle_action_should_report_new_items_as_current_user() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.create_epic = Box::new(|| Epic::new("".to_string(), "".to_string()));
+        nav.set_prompts(prompts);
+        nav.set_user(Some("jdoe".to_string()));
+        // not in the registry yet, so nobody is recorded
+        nav.handle_action(Action::CreateEpic).unwrap();
+        db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+            .unwrap();
+        nav.handle_action(Action::CreateEpic).unwrap();
+
+        let epics = db.read_db().unwrap().epics;
+        assert_eq!(epics[&1].reporter, None);
+        assert_eq!(epics[&2].reporter.as_deref(), Some("jdoe"));
+    }
+
+    #[test]
+    fn handle_action_should_handle_assign_story() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+            .unwrap();
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.assign_story = Box::new(|story| Story {
+            assignee: Some("jdoe".to_string()),
+            ..story.clone()
         });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::AssignStory { story_id }).unwrap();
+
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].assignee.as_deref(),
+            Some("jdoe")
+        );
+    }
+
+    #[test]
+    fn handle_action_should_label_story_and_filter_by_label() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_string(), "".to_string()))
             .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
         let mut nav = Navigator::new(Rc::clone(&db));
         let mut prompts = Prompts::new();
 
-        prompts.update_status = Box::new(|| Some(Status::InProgress));
+        prompts.add_label = Box::new(|| Some("Backend".to_string()));
+        prompts.filter_label = Box::new(|labels| {
+            assert_eq!(labels, [("backend".to_string(), 1)]);
+            Some(labels[0].0.clone())
+        });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::AddStoryLabel { story_id })
+            .unwrap();
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+            .unwrap();
+        nav.handle_action(Action::FilterByLabel).unwrap();
+
+        assert_eq!(db.read_db().unwrap().stories[&story_id].labels, ["backend"]);
+        let epic_detail = nav
+            .get_current_page()
+            .unwrap()
+            .as_any()
+            .downcast_ref::<EpicDetail>()
+            .unwrap();
+        assert_eq!(epic_detail.filter.borrow().as_deref(), Some("backend"));
+    }
+
+    #[test]
+    fn handle_action_should_offer_estimate_scale() {
+        let db = Rc::new(
+            JiraDatabase::new(Box::new(MockDB::new()))
+                .with_estimate_scale(serde_json::from_str("[1, 2, 4]").unwrap()),
+        );
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.estimate_story = Box::new(|scale, current| {
+            assert_eq!(scale, [1, 2, 4]);
+            assert_eq!(current, None);
+            Some(4)
+        });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::EstimateStory { story_id })
+            .unwrap();
+
+        assert_eq!(db.read_db().unwrap().stories[&story_id].estimate, Some(4));
+    }
+
+    #[test]
+    fn handle_action_should_warn_before_resolving_blocked_story() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let [blocker, story_id] = [(); 2].map(|_| {
+            db.create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap()
+        });
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.link_item = Box::new(move |story_id| Some((blocker, LinkKind::Blocks, story_id)));
+        prompts.update_status = Box::new(|_| Some(Status::new("closed")));
+        prompts.resolve_blocked = Box::new(move |blockers| {
+            assert_eq!(blockers, [blocker]);
+            false
+        });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::LinkStory { story_id }).unwrap();
+        nav.handle_action(Action::UpdateStoryStatus { story_id })
+            .unwrap();
+
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::new("open")
+        );
+
+        let mut prompts = Prompts::new();
+        prompts.update_status = Box::new(|_| Some(Status::new("closed")));
+        prompts.resolve_blocked = Box::new(|_| true);
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::UpdateStoryStatus { story_id })
+            .unwrap();
+
+        assert_eq!(
+            db.read_db().unwrap().stories[&story_id].status,
+            Status::new("closed")
+        );
+    }
+
+    #[test]
+    fn handle_action_should_offer_inbound_and_outbound_links() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let [a, b] = [(); 2].map(|_| {
+            db.create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap()
+        });
+        db.link(a, LinkKind::Blocks, b).unwrap();
+        db.link(b, LinkKind::RelatesTo, epic_id).unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.unlink_item = Box::new(move |links| {
+            assert_eq!(
+                links,
+                [(b, LinkKind::RelatesTo, epic_id), (a, LinkKind::Blocks, b)]
+            );
+            Some(1)
+        });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::UnlinkStory { story_id: b })
+            .unwrap();
+
+        assert!(db.open_blockers(b).unwrap().is_empty());
+        assert!(nav
+            .handle_action(Action::UnlinkStory { story_id: a })
+            .is_err());
+    }
+
+    #[test]
+    fn handle_action_should_link_and_unlink_epics() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let [blocker, epic_id] = [(); 2].map(|_| {
+            db.create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap()
+        });
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.link_item = Box::new(move |id| Some((blocker, LinkKind::Blocks, id)));
+        prompts.unlink_item = Box::new(move |links| {
+            assert_eq!(links, [(blocker, LinkKind::Blocks, epic_id)]);
+            Some(0)
+        });
+        nav.set_prompts(prompts);
+
+        nav.handle_action(Action::LinkEpic { epic_id }).unwrap();
+        assert_eq!(db.open_blockers(epic_id).unwrap(), [blocker]);
+
+        nav.handle_action(Action::UnlinkEpic { epic_id }).unwrap();
+        assert!(db.read_db().unwrap().links.is_empty());
+        assert!(nav.handle_action(Action::UnlinkEpic { epic_id }).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_manage_subtasks() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.toggle_subtask = Box::new(|_| panic!("nothing to offer"));
+        nav.set_prompts(prompts);
+        assert!(nav
+            .handle_action(Action::ToggleSubtask { story_id })
+            .is_err());
+
+        let mut prompts = Prompts::new();
+        prompts.add_subtask = Box::new(|| Some("write".to_string()));
+        prompts.toggle_subtask = Box::new(|subtasks| {
+            assert_eq!(subtasks, [Subtask::new("write".to_string())]);
+            Some(0)
+        });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::AddSubtask { story_id }).unwrap();
+        nav.handle_action(Action::ToggleSubtask { story_id })
+            .unwrap();
+
+        assert!(db.read_db().unwrap().stories[&story_id].subtasks[0].done);
+    }
+
+    #[test]
+    fn handle_action_should_only_offer_own_comments() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+            .unwrap();
+        db.add_user("rroe".to_string(), "Richard Roe".to_string())
+            .unwrap();
+        db.add_comment(story_id, "rroe".to_string(), "first".to_string())
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.add_comment = Box::new(|| Some("second".to_string()));
+        prompts.edit_comment = Box::new(|comments| {
+            assert_eq!(comments.len(), 1);
+            assert_eq!(comments[0].author, "jdoe");
+            Some((comments[0].id, "edited".to_string()))
+        });
+        prompts.delete_comment = Box::new(|_| panic!("nothing to offer"));
+        nav.set_prompts(prompts);
+
+        assert!(nav.handle_action(Action::AddComment { story_id }).is_err());
+
+        nav.set_user(Some("jdoe".to_string()));
+        nav.handle_action(Action::AddComment { story_id }).unwrap();
+        nav.handle_action(Action::EditComment { story_id }).unwrap();
+
+        let comments = &db.read_db().unwrap().comments[&story_id];
+        assert_eq!(comments[1].body, "edited");
+
+        nav.set_user(Some("nobody".to_string()));
+        assert!(nav
+            .handle_action(Action::DeleteComment { story_id })
+            .is_err());
+    }
+
+    #[test]
+    fn handle_action_should_not_remove_label_from_unlabelled_epic() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.remove_label = Box::new(|_| panic!("nothing to offer"));
+        nav.set_prompts(prompts);
+
+        assert!(nav
+            .handle_action(Action::RemoveEpicLabel { epic_id })
+            .is_err());
+    }
+
+    #[test]
+    fn handle_action_should_open_my_work_only_with_user() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let mut nav = Navigator::new(Rc::clone(&db));
+
+        assert!(nav.handle_action(Action::NavigateToMyWork).is_err());
+        assert_eq!(nav.get_page_count(), 1);
+
+        nav.set_user(Some("jdoe".to_string()));
+        nav.handle_action(Action::NavigateToMyWork).unwrap();
+
+        assert!(nav
+            .get_current_page()
+            .unwrap()
+            .as_any()
+            .downcast_ref::<MyWork>()
+            .is_some());
+    }
+
+    #[test]
+    fn handle_action_should_handle_edit_epic() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("Tpyo".to_string(), "kept".to_string()))
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.update_epic = Box::new(|epic| Epic {
+            name: "Typo".to_string(),
+            ..epic.clone()
+        });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::UpdateEpic { epic_id }).unwrap();
+
+        let epic = &db.read_db().unwrap().epics[&epic_id];
+        assert_eq!(epic.name, "Typo");
+        assert_eq!(epic.description, "kept");
+    }
+
+    #[test]
+    fn handle_action_should_handle_edit_story() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("kept".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.update_story = Box::new(|story| Story {
+            description: "filled in".to_string(),
+            ..story.clone()
+        });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::UpdateStory { story_id }).unwrap();
+
+        let story = &db.read_db().unwrap().stories[&story_id];
+        assert_eq!(story.name, "kept");
+        assert_eq!(story.description, "filled in");
+    }
+
+    #[test]
+    fn handle_action_should_move_story_and_follow_it() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let from_epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let to_epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), from_epic_id)
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.move_story = Box::new(move || Some(to_epic_id));
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::NavigateToEpicDetail {
+            epic_id: from_epic_id,
+        })
+        .unwrap();
+        nav.handle_action(Action::NavigateToStoryDetail {
+            epic_id: from_epic_id,
+            story_id,
+        })
+        .unwrap();
+        nav.handle_action(Action::MoveStory {
+            epic_id: from_epic_id,
+            story_id,
+        })
+        .unwrap();
+
+        assert_eq!(
+            db.read_db().unwrap().epics[&to_epic_id].stories,
+            vec![story_id]
+        );
+        assert_eq!(nav.get_page_count(), 3);
+        let story_page = nav.get_current_page().unwrap().as_any();
+        assert_eq!(
+            story_page.downcast_ref::<StoryDetail>().unwrap().epic_id,
+            to_epic_id
+        );
+        nav.handle_action(Action::NavigateToPreviousPage).unwrap();
+        let epic_page = nav.get_current_page().unwrap().as_any();
+        assert_eq!(
+            epic_page.downcast_ref::<EpicDetail>().unwrap().epic_id,
+            to_epic_id
+        );
+    }
+
+    #[test]
+    fn handle_action_should_keep_page_when_move_target_is_missing() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.move_story = Box::new(|| Some(999));
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::NavigateToStoryDetail { epic_id, story_id })
+            .unwrap();
+
+        let error = nav
+            .handle_action(Action::MoveStory { epic_id, story_id })
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<JiraError>(),
+            Some(JiraError::EpicNotFound(999))
+        ));
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_handle_update_epic() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.update_status = Box::new(|_| Some(Status::new("in_progress")));
         nav.set_prompts(prompts);
         nav.handle_action(Action::UpdateEpicStatus { epic_id })
             .unwrap();
 
         assert_eq!(
             db.read_db().unwrap().epics.get(&epic_id).unwrap().status,
-            Status::InProgress
+            Status::new("in_progress")
         );
     }
 
     #[test]
     fn handle_action_should_handle_delete_epic() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.delete_epic = Box::new(|| true);
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
+
+        assert_eq!(db.read_db().unwrap().epics.len(), 0);
+    }
+
+    #[test]
+    fn handle_action_should_handle_undo_and_redo() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_string(), "".to_string()))
             .unwrap();
@@ -250,14 +1161,76 @@ mod tests {
         nav.set_prompts(prompts);
         nav.handle_action(Action::DeleteEpic { epic_id }).unwrap();
 
+        nav.handle_action(Action::Undo).unwrap();
+        assert!(db.read_db().unwrap().epics.contains_key(&epic_id));
+
+        nav.handle_action(Action::Redo).unwrap();
         assert_eq!(db.read_db().unwrap().epics.len(), 0);
+
+        assert!(nav.handle_action(Action::Redo).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_leave_pages_removed_by_undo() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+            .unwrap();
+        nav.handle_action(Action::Undo).unwrap();
+
+        assert_eq!(nav.get_page_count(), 1);
+        assert!(nav.get_current_page().unwrap().as_any().is::<HomePage>());
+    }
+
+    #[test]
+    fn handle_action_should_point_story_page_at_its_epic_after_undoing_a_move() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let from_epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let to_epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), from_epic_id)
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.move_story = Box::new(move || Some(to_epic_id));
+        prompts.delete_story = Box::new(|| true);
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::NavigateToStoryDetail {
+            epic_id: from_epic_id,
+            story_id,
+        })
+        .unwrap();
+        nav.handle_action(Action::MoveStory {
+            epic_id: from_epic_id,
+            story_id,
+        })
+        .unwrap();
+        nav.handle_action(Action::Undo).unwrap();
+
+        let story_page = nav.get_current_page().unwrap().as_any();
+        let story_page = story_page.downcast_ref::<StoryDetail>().unwrap();
+        assert_eq!(story_page.epic_id, from_epic_id);
+
+        let action = Action::DeleteStory {
+            epic_id: story_page.epic_id,
+            story_id,
+        };
+        nav.handle_action(action).unwrap();
+        assert!(db.read_db().unwrap().stories.is_empty());
     }
 
     #[test]
     fn handle_action_should_handle_create_story() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_string(), "".to_string()))
             .unwrap();
@@ -279,9 +1252,7 @@ mod tests {
 
     #[test]
     fn handle_action_should_handle_update_story() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        });
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_string(), "".to_string()))
             .unwrap();
@@ -292,22 +1263,61 @@ mod tests {
         let mut nav = Navigator::new(Rc::clone(&db));
         let mut prompts = Prompts::new();
 
-        prompts.update_status = Box::new(|| Some(Status::InProgress));
+        prompts.update_status = Box::new(|_| Some(Status::new("in_progress")));
         nav.set_prompts(prompts);
         nav.handle_action(Action::UpdateStoryStatus { story_id })
             .unwrap();
 
         assert_eq!(
             db.read_db().unwrap().stories.get(&story_id).unwrap().status,
-            Status::InProgress
+            Status::new("in_progress")
         );
     }
 
     #[test]
-    fn handle_action_should_handle_delete_story() {
-        let db = Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
+    fn handle_action_should_offer_only_allowed_statuses() {
+        let workflows: Workflows = serde_json::from_str(
+            r#"{ "story": {
+                "initial": "todo",
+                "states": [
+                    { "id": "todo", "name": "To Do", "category": "todo" },
+                    { "id": "review", "name": "Review", "category": "in_progress" },
+                    { "id": "done", "name": "Done", "category": "done" }
+                ],
+                "transitions": { "todo": ["review"], "review": ["done"] }
+            } }"#,
+        )
+        .unwrap();
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())).with_workflows(workflows));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.update_status = Box::new(|targets| {
+            let ids: Vec<_> = targets.iter().map(|state| state.id.to_string()).collect();
+            assert_eq!(ids, vec!["review"]);
+            Some(targets[0].id.clone())
         });
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::UpdateStoryStatus { story_id })
+            .unwrap();
+        db.update_story_status(story_id, Status::new("done"))
+            .unwrap();
+
+        let error = nav
+            .handle_action(Action::UpdateStoryStatus { story_id })
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Done is a final state");
+    }
+
+    #[test]
+    fn handle_action_should_handle_delete_story() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
         let epic_id = db
             .create_epic(Epic::new("".to_string(), "".to_string()))
             .unwrap();
@@ -327,9 +1337,9 @@ mod tests {
 
     #[test]
     fn handle_action_should_clear_pages_on_exit() {
-        let mut nav = Navigator::new(Rc::clone(&Rc::new(JiraDatabase {
-            database: Box::new(MockDB::new()),
-        })));
+        let mut nav = Navigator::new(Rc::clone(&Rc::new(JiraDatabase::new(Box::new(
+            MockDB::new(),
+        )))));
 
         nav.handle_action(Action::NavigateToEpicDetail { epic_id: 1 })
             .unwrap();
@@ -342,4 +1352,92 @@ mod tests {
 
         assert_eq!(nav.get_page_count(), 0);
     }
+
+    #[test]
+    fn handle_action_should_propagate_write_errors() {
+        let db = JiraDatabase::new(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let failing_db = Rc::new(JiraDatabase::new(Box::new(FailingDB::new(
+            db.read_db().unwrap(),
+        ))));
+
+        let mut nav = Navigator::new(Rc::clone(&failing_db));
+        let mut prompts = Prompts::new();
+
+        prompts.create_epic = Box::new(|| Epic::new("".to_string(), "".to_string()));
+        prompts.create_story = Box::new(|| Story::new("".to_string(), "".to_string()));
+        prompts.update_status = Box::new(|_| Some(Status::new("closed")));
+        prompts.delete_epic = Box::new(|| true);
+        prompts.delete_story = Box::new(|| true);
+        nav.set_prompts(prompts);
+
+        assert!(nav.handle_action(Action::CreateEpic).is_err());
+        assert!(nav.handle_action(Action::CreateStory { epic_id }).is_err());
+        assert!(nav
+            .handle_action(Action::UpdateEpicStatus { epic_id })
+            .is_err());
+        assert!(nav
+            .handle_action(Action::UpdateStoryStatus { story_id })
+            .is_err());
+        assert!(nav
+            .handle_action(Action::DeleteStory { epic_id, story_id })
+            .is_err());
+        assert!(nav.handle_action(Action::DeleteEpic { epic_id }).is_err());
+    }
+
+    #[test]
+    fn handle_action_should_keep_page_when_delete_fails() {
+        let db = JiraDatabase::new(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let failing_db = Rc::new(JiraDatabase::new(Box::new(FailingDB::new(
+            db.read_db().unwrap(),
+        ))));
+
+        let mut nav = Navigator::new(Rc::clone(&failing_db));
+        let mut prompts = Prompts::new();
+
+        prompts.delete_epic = Box::new(|| true);
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+            .unwrap();
+
+        assert!(nav.handle_action(Action::DeleteEpic { epic_id }).is_err());
+        assert_eq!(nav.get_page_count(), 2);
+    }
+
+    #[test]
+    fn handle_action_should_leave_page_of_missing_story() {
+        let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+        let epic_id = db
+            .create_epic(Epic::new("".to_string(), "".to_string()))
+            .unwrap();
+        let mut nav = Navigator::new(Rc::clone(&db));
+        let mut prompts = Prompts::new();
+
+        prompts.update_status = Box::new(|_| Some(Status::new("closed")));
+        nav.set_prompts(prompts);
+        nav.handle_action(Action::NavigateToEpicDetail { epic_id })
+            .unwrap();
+        nav.handle_action(Action::NavigateToStoryDetail {
+            epic_id,
+            story_id: 999,
+        })
+        .unwrap();
+
+        let error = nav
+            .handle_action(Action::UpdateStoryStatus { story_id: 999 })
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<JiraError>(),
+            Some(JiraError::StoryNotFound(999))
+        ));
+        assert_eq!(nav.get_page_count(), 2);
+    }
 }
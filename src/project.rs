@@ -0,0 +1,402 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::Backend,
+    error::{JiraError, Result},
+    estimate::EstimateScale,
+    workflow::Workflows,
+};
+
+// found by walking up from the working directory, like git does with `.git`
+pub const PROJECT_DIR: &str = ".jira";
+pub const CONFIG_FILE: &str = "config.json";
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<Backend>,
+    // relative paths are resolved against the directory holding the config file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflows: Option<Workflows>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate_scale: Option<EstimateScale>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let invalid = |error: String| JiraError::InvalidConfig(path.display().to_string(), error);
+
+        let content = fs::read_to_string(path)?;
+        let config: Config =
+            serde_json::from_str(&content).map_err(|error| invalid(error.to_string()))?;
+        if let Some(workflows) = &config.workflows {
+            workflows.validate().map_err(invalid)?;
+        }
+        if let Some(estimate_scale) = &config.estimate_scale {
+            estimate_scale.validate().map_err(invalid)?;
+        }
+
+        Ok(config)
+    }
+}
+
+// where the board lives once flags, environment, config and discovery have had their say
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub backend: Backend,
+    pub file_path: String,
+    // the board's workflows travel with it, they come from the same config file
+    pub workflows: Workflows,
+    pub estimate_scale: EstimateScale,
+}
+
+impl Location {
+    // a sqlite board that is not there yet gets imported from the db.json next to it on open
+    pub fn exists(&self) -> bool {
+        let path = Path::new(&self.file_path);
+
+        match self.backend {
+            Backend::Json => path.exists(),
+            Backend::Sqlite => path.exists() || path.with_extension("json").exists(),
+        }
+    }
+}
+
+// what the user asked for explicitly, through flags or environment variables
+#[derive(Debug, Default)]
+pub struct Overrides {
+    pub backend: Option<Backend>,
+    pub database: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+}
+
+pub fn find_project_dir(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_DIR))
+        .find(|dir| dir.is_dir())
+}
+
+pub fn resolve(overrides: Overrides, cwd: &Path) -> Result<Location> {
+    let project_dir = find_project_dir(cwd);
+
+    let config_path = match (&overrides.config, &project_dir) {
+        (Some(config_path), _) => Some(cwd.join(config_path)),
+        (None, Some(project_dir)) => Some(project_dir.join(CONFIG_FILE)),
+        (None, None) => None,
+    };
+    let config = match &config_path {
+        Some(config_path) => match Config::load(config_path) {
+            Ok(config) => config,
+            // a project without a config file just uses the defaults
+            Err(JiraError::Io(error))
+                if error.kind() == ErrorKind::NotFound && overrides.config.is_none() =>
+            {
+                Config::default()
+            }
+            Err(error) => return Err(error),
+        },
+        None => Config::default(),
+    };
+
+    let database = match (overrides.database, config.database, &config_path) {
+        (Some(database), _, _) => Some(cwd.join(database)),
+        (None, Some(database), Some(config_path)) => Some(
+            config_path
+                .parent()
+                .map(|dir| dir.join(&database))
+                .unwrap_or(database),
+        ),
+        _ => None,
+    };
+
+    let backend = overrides
+        .backend
+        .or(config.backend)
+        .or_else(|| database.as_deref().map(backend_for))
+        .unwrap_or(Backend::Json);
+
+    let file_path = match (database, project_dir) {
+        (Some(database), _) => database,
+        (None, Some(project_dir)) => project_dir.join(default_file_name(backend)),
+        // boards created before `.jira` existed live in `data/` under the working directory
+        (None, None) => cwd.join("data").join(default_file_name(backend)),
+    };
+
+    Ok(Location {
+        backend,
+        file_path: file_path.to_string_lossy().to_string(),
+        workflows: config.workflows.unwrap_or_default(),
+        estimate_scale: config.estimate_scale.unwrap_or_default(),
+    })
+}
+
+// `init` sets up a project in the working directory rather than in whatever parent it would
+// otherwise discover
+pub fn init_location(backend: Option<Backend>, database: Option<PathBuf>, cwd: &Path) -> Location {
+    let backend = backend
+        .or_else(|| database.as_deref().map(backend_for))
+        .unwrap_or(Backend::Json);
+    let file_path = match database {
+        Some(database) => cwd.join(database),
+        None => cwd.join(PROJECT_DIR).join(default_file_name(backend)),
+    };
+
+    Location {
+        backend,
+        file_path: file_path.to_string_lossy().to_string(),
+        workflows: Workflows::default(),
+        estimate_scale: EstimateScale::default(),
+    }
+}
+
+pub fn default_file_name(backend: Backend) -> &'static str {
+    match backend {
+        Backend::Json => "db.json",
+        Backend::Sqlite => "db.sqlite",
+    }
+}
+
+fn backend_for(database: &Path) -> Backend {
+    match database
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some("sqlite" | "db") => Backend::Sqlite,
+        _ => Backend::Json,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_should_find_project_dir_in_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+
+        let location = resolve(Overrides::default(), &dir.path().join("src/nested")).unwrap();
+
+        assert_eq!(
+            location,
+            Location {
+                backend: Backend::Json,
+                file_path: path_in(&dir, ".jira/db.json"),
+                workflows: Workflows::default(),
+                estimate_scale: EstimateScale::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_should_fall_back_to_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let location = resolve(Overrides::default(), dir.path()).unwrap();
+
+        assert_eq!(location.file_path, path_in(&dir, "data/db.json"));
+    }
+
+    #[test]
+    fn resolve_should_read_config_relative_to_itself() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        fs::write(
+            dir.path().join(".jira/config.json"),
+            r#"{ "database": "../boards/team.sqlite" }"#,
+        )
+        .unwrap();
+
+        let location = resolve(Overrides::default(), &dir.path().join("sub")).unwrap();
+
+        assert_eq!(location.backend, Backend::Sqlite);
+        assert_eq!(
+            Path::new(&location.file_path),
+            dir.path().join(".jira/../boards/team.sqlite")
+        );
+    }
+
+    #[test]
+    fn resolve_should_prefer_overrides_over_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::write(
+            dir.path().join(".jira/config.json"),
+            r#"{ "backend": "sqlite", "database": "board.sqlite" }"#,
+        )
+        .unwrap();
+        let overrides = Overrides {
+            backend: Some(Backend::Json),
+            database: Some(PathBuf::from("elsewhere.json")),
+            config: None,
+        };
+
+        let location = resolve(overrides, dir.path()).unwrap();
+
+        assert_eq!(
+            location,
+            Location {
+                backend: Backend::Json,
+                file_path: path_in(&dir, "elsewhere.json"),
+                workflows: Workflows::default(),
+                estimate_scale: EstimateScale::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_should_pick_backend_file_in_project_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        let overrides = Overrides {
+            backend: Some(Backend::Sqlite),
+            ..Overrides::default()
+        };
+
+        let location = resolve(overrides, dir.path()).unwrap();
+
+        assert_eq!(location.file_path, path_in(&dir, ".jira/db.sqlite"));
+    }
+
+    #[test]
+    fn resolve_should_fail_on_missing_explicit_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let overrides = Overrides {
+            config: Some(PathBuf::from("missing.json")),
+            ..Overrides::default()
+        };
+
+        assert!(matches!(
+            resolve(overrides, dir.path()),
+            Err(JiraError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn resolve_should_fail_on_invalid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::write(
+            dir.path().join(".jira/config.json"),
+            r#"{ "backend": "csv" }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            resolve(Overrides::default(), dir.path()),
+            Err(JiraError::InvalidConfig(..))
+        ));
+    }
+
+    #[test]
+    fn resolve_should_load_workflows_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::write(
+            dir.path().join(".jira/config.json"),
+            r#"{ "workflows": { "story": {
+                "initial": "todo",
+                "states": [
+                    { "id": "todo", "name": "To Do", "category": "todo" },
+                    { "id": "done", "name": "Done", "category": "done" }
+                ]
+            } } }"#,
+        )
+        .unwrap();
+
+        let location = resolve(Overrides::default(), dir.path()).unwrap();
+
+        assert_eq!(location.workflows.story.initial.to_string(), "todo");
+        assert_eq!(location.workflows.epic, Default::default());
+    }
+
+    #[test]
+    fn resolve_should_load_estimate_scale_from_config() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::write(
+            dir.path().join(".jira/config.json"),
+            r#"{ "estimate_scale": [1, 2, 4, 8] }"#,
+        )
+        .unwrap();
+
+        let location = resolve(Overrides::default(), dir.path()).unwrap();
+        assert_eq!(location.estimate_scale.points(), [1, 2, 4, 8]);
+
+        fs::write(
+            dir.path().join(".jira/config.json"),
+            r#"{ "estimate_scale": [8, 4] }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            resolve(Overrides::default(), dir.path()),
+            Err(JiraError::InvalidConfig(..))
+        ));
+    }
+
+    #[test]
+    fn resolve_should_fail_on_invalid_workflow() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::write(
+            dir.path().join(".jira/config.json"),
+            r#"{ "workflows": { "epic": { "initial": "backlog", "states": [] } } }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            resolve(Overrides::default(), dir.path()),
+            Err(JiraError::InvalidConfig(..))
+        ));
+    }
+
+    #[test]
+    fn init_location_should_ignore_parent_project() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".jira")).unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+
+        let location = init_location(Some(Backend::Sqlite), None, &dir.path().join("sub"));
+
+        assert_eq!(
+            location,
+            Location {
+                backend: Backend::Sqlite,
+                file_path: path_in(&dir, "sub/.jira/db.sqlite"),
+                workflows: Workflows::default(),
+                estimate_scale: EstimateScale::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn location_should_count_json_to_import_as_sqlite_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = Location {
+            backend: Backend::Sqlite,
+            file_path: path_in(&dir, "db.sqlite"),
+            workflows: Workflows::default(),
+            estimate_scale: EstimateScale::default(),
+        };
+        assert!(!location.exists());
+
+        fs::write(dir.path().join("db.json"), "{}").unwrap();
+
+        assert!(location.exists());
+    }
+
+    fn path_in(dir: &tempfile::TempDir, relative: &str) -> String {
+        dir.path().join(relative).to_string_lossy().to_string()
+    }
+}
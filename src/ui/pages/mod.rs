@@ -1,9 +1,24 @@
-use std::{any::Any, rc::Rc};
-
-use anyhow::{anyhow, Result};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use itertools::Itertools;
 
-use crate::{db::JiraDatabase, models::Action, ui::pages::page_helpers::get_column_string};
+use crate::{
+    db::{links, JiraDatabase},
+    error::JiraError,
+    estimate::Points,
+    models::{Action, Comment, DBState, Priority, Status, Subtask},
+    ui::pages::page_helpers::{
+        compare, due_soon_horizon, due_state, get_column_string, highlight_overdue, Due,
+        SortFields, SortKey,
+    },
+    workflow::{Category, Workflow, Workflows},
+};
 
 mod page_helpers;
 
@@ -15,27 +30,68 @@ pub trait Page {
 
 pub struct HomePage {
     pub db: Rc<JiraDatabase>,
+    pub sort: Cell<SortKey>,
+    // only epics with this label are listed, see `Action::FilterByLabel`
+    pub filter: RefCell<Option<String>>,
 }
 
 impl Page for HomePage {
     fn draw_page(&self) -> Result<()> {
         println!("----------------------------- EPICS ------------------------------");
-        println!("     id     |               name               |      status      ");
+        print_filter(&self.filter);
+        println!("{}| done ", ITEM_HEADER);
 
-        let epics = self.db.read_db()?.epics;
+        let db_state = self.db.read_db()?;
+        let epics = &db_state.epics;
+        let workflows = self.db.workflows();
+        let workflow = &workflows.epic;
+        let today = self.db.today();
 
-        epics.keys().sorted().into_iter().for_each(|epic_id| {
-            let epic = &epics[epic_id];
-            let id_col = get_column_string(&epic_id.to_string(), 11);
-            let name_col = get_column_string(&epic.name, 32);
-            let status_col = get_column_string(&epic.status.to_string(), 17);
-            println!("{} | {} | {}", id_col, name_col, status_col);
-        });
+        epics
+            .iter()
+            .filter(|(_, epic)| matches_filter(&self.filter, &epic.labels))
+            .sorted_by(|(a_id, a), (b_id, b)| {
+                compare(
+                    self.sort.get(),
+                    &SortFields {
+                        id: **a_id,
+                        name: &a.name,
+                        priority: a.priority,
+                        status: workflow.rank(&a.status),
+                    },
+                    &SortFields {
+                        id: **b_id,
+                        name: &b.name,
+                        priority: b.priority,
+                        status: workflow.rank(&b.status),
+                    },
+                )
+            })
+            .for_each(|(epic_id, epic)| {
+                let row = item_row(
+                    *epic_id,
+                    &epic.name,
+                    &epic.assignee,
+                    epic.priority,
+                    &workflow.name_of(&epic.status),
+                    epic.due_date,
+                );
+                let points = Points::of_epic(epic, &db_state.stories, &workflows.story);
+                let done = points
+                    .percent_done()
+                    .map_or("-".to_string(), |percent| format!("{percent}%"));
+                let row = format!("{} | {}", row, get_column_string(&done, 5));
+                let due = due_state(epic.due_date, is_done(workflow, &epic.status), today);
+                println!("{}", highlight_overdue(row, due));
+            });
 
         println!();
         println!();
 
-        println!("[q] quit | [c] create epic | [:id:] navigate to epic");
+        println!(
+            "[q] quit | [c] create epic | [:id:] navigate to epic | [w] my work | [o] overdue & due soon | [s] sort: {} | [f] filter by label | [z] undo | [y] redo",
+            self.sort.get().label()
+        );
 
         Ok(())
     }
@@ -46,6 +102,15 @@ impl Page for HomePage {
         match input {
             "q" => Ok(Some(Action::Exit)),
             "c" => Ok(Some(Action::CreateEpic)),
+            "w" => Ok(Some(Action::NavigateToMyWork)),
+            "o" => Ok(Some(Action::NavigateToDueDates)),
+            "f" => Ok(Some(Action::FilterByLabel)),
+            "s" => {
+                self.sort.set(self.sort.get().next());
+                Ok(None)
+            }
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
             input => {
                 if let Ok(epic_id) = input.parse::<u32>() {
                     if epics.contains_key(&epic_id) {
@@ -65,6 +130,9 @@ impl Page for HomePage {
 pub struct EpicDetail {
     pub epic_id: u32,
     pub db: Rc<JiraDatabase>,
+    pub sort: Cell<SortKey>,
+    // only stories with this label are listed
+    pub filter: RefCell<Option<String>>,
 }
 
 impl Page for EpicDetail {
@@ -73,7 +141,8 @@ impl Page for EpicDetail {
         let epic = db_state
             .epics
             .get(&self.epic_id)
-            .ok_or_else(|| anyhow!("could not find epic!"))?;
+            .ok_or(JiraError::EpicNotFound(self.epic_id))?;
+        let workflows = self.db.workflows();
 
         println!("------------------------------ EPIC ------------------------------");
         println!("  id  |     name     |         description         |    status    ");
@@ -81,49 +150,123 @@ impl Page for EpicDetail {
         let id_col = get_column_string(&self.epic_id.to_string(), 5);
         let name_col = get_column_string(&epic.name, 12);
         let desc_col = get_column_string(&epic.description, 27);
-        let status_col = get_column_string(&epic.status.to_string(), 13);
+        let status_col = get_column_string(&workflows.epic.name_of(&epic.status), 13);
         println!("{} | {} | {} | {}", id_col, name_col, desc_col, status_col);
+        println!(
+            "{}",
+            details_line(epic.priority, &epic.assignee, &epic.reporter, epic.due_date)
+        );
+        println!("{}", labels_line(&epic.labels));
+        println!("{}", timestamps_line(&epic.created_at, &epic.updated_at));
+        let points = Points::of_epic(epic, &db_state.stories, &workflows.story);
+        println!(
+            "points: {} total | {} completed | {} remaining",
+            points.total,
+            points.completed,
+            points.remaining()
+        );
+
+        println!();
+        print_links(&db_state, workflows, self.epic_id);
 
         println!();
 
         println!("---------------------------- STORIES -----------------------------");
-        println!("     id     |               name               |      status      ");
+        print_filter(&self.filter);
+        println!("{}| subtasks ", ITEM_HEADER);
 
         let stories = &db_state.stories;
+        let today = self.db.today();
+        // a dangling id is shown rather than trusted, `fsck --repair` cleans it up
+        let (known, missing): (Vec<u32>, Vec<u32>) = epic
+            .stories
+            .iter()
+            .partition(|story_id| stories.contains_key(story_id));
 
-        epic.stories
+        known
             .iter()
-            .sorted()
-            .into_iter()
-            .for_each(|epic_id| {
-                let story = &stories[epic_id];
-                let id_col = get_column_string(&epic_id.to_string(), 11);
-                let name_col = get_column_string(&story.name, 32);
-                let status_col = get_column_string(&story.status.to_string(), 17);
-                println!("{} | {} | {}", id_col, name_col, status_col);
+            .map(|story_id| (story_id, &stories[story_id]))
+            .filter(|(_, story)| matches_filter(&self.filter, &story.labels))
+            .sorted_by(|(a_id, a), (b_id, b)| {
+                compare(
+                    self.sort.get(),
+                    &SortFields {
+                        id: **a_id,
+                        name: &a.name,
+                        priority: a.priority,
+                        status: workflows.story.rank(&a.status),
+                    },
+                    &SortFields {
+                        id: **b_id,
+                        name: &b.name,
+                        priority: b.priority,
+                        status: workflows.story.rank(&b.status),
+                    },
+                )
+            })
+            .for_each(|(story_id, story)| {
+                let row = item_row(
+                    *story_id,
+                    &story.name,
+                    &story.assignee,
+                    story.priority,
+                    &workflows.story.name_of(&story.status),
+                    story.due_date,
+                );
+                let row = epic_story_row(row, &subtask_progress(&story.subtasks));
+                let done = is_done(&workflows.story, &story.status);
+                println!(
+                    "{}",
+                    highlight_overdue(row, due_state(story.due_date, done, today))
+                );
             });
+        missing.iter().sorted().for_each(|story_id| {
+            let row = item_cells(&story_id.to_string(), "<missing story>", "", "", "?", "");
+            println!("{}", epic_story_row(row, ""));
+        });
 
         println!();
         println!();
 
-        println!("[p] previous | [u] update epic | [d] delete epic | [c] create story | [:id:] navigate to story");
+        println!(
+            "[p] previous | [e] edit epic | [u] update epic | [a] assign epic | [l] add label | [x] remove label | [n] link | [b] unlink | [d] delete epic | [c] create story | [:id:] navigate to story | [s] sort: {} | [f] filter by label | [z] undo | [y] redo",
+            self.sort.get().label()
+        );
 
         Ok(())
     }
 
     fn handle_input(&self, input: &str) -> Result<Option<Action>> {
         let db_state = self.db.read_db()?;
-        let stories = db_state.stories;
+        let epic = db_state
+            .epics
+            .get(&self.epic_id)
+            .ok_or(JiraError::EpicNotFound(self.epic_id))?;
         let epic_id = self.epic_id;
 
         match input {
             "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "e" => Ok(Some(Action::UpdateEpic { epic_id })),
             "u" => Ok(Some(Action::UpdateEpicStatus { epic_id })),
+            "a" => Ok(Some(Action::AssignEpic { epic_id })),
+            "l" => Ok(Some(Action::AddEpicLabel { epic_id })),
+            "x" => Ok(Some(Action::RemoveEpicLabel { epic_id })),
+            "n" => Ok(Some(Action::LinkEpic { epic_id })),
+            "b" => Ok(Some(Action::UnlinkEpic { epic_id })),
             "d" => Ok(Some(Action::DeleteEpic { epic_id })),
             "c" => Ok(Some(Action::CreateStory { epic_id })),
+            "f" => Ok(Some(Action::FilterByLabel)),
+            "s" => {
+                self.sort.set(self.sort.get().next());
+                Ok(None)
+            }
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
             input => {
                 if let Ok(story_id) = input.parse::<u32>() {
-                    if stories.contains_key(&story_id) {
+                    // only this epic's own stories, a moved story belongs to its new epic
+                    if epic.stories.contains(&story_id) && db_state.stories.contains_key(&story_id)
+                    {
                         return Ok(Some(Action::NavigateToStoryDetail { epic_id, story_id }));
                     }
                 }
@@ -149,20 +292,64 @@ impl Page for StoryDetail {
         let story = db_state
             .stories
             .get(&self.story_id)
-            .ok_or_else(|| anyhow!("could not find story!"))?;
+            .ok_or(JiraError::StoryNotFound(self.story_id))?;
 
         println!("------------------------------ STORY ------------------------------");
         println!("  id  |     name     |         description         |    status    ");
         let id_col = get_column_string(&self.story_id.to_string(), 5);
         let name_col = get_column_string(&story.name, 12);
         let desc_col = get_column_string(&story.description, 27);
-        let status_col = get_column_string(&story.status.to_string(), 13);
+        let status_col = get_column_string(&self.db.workflows().story.name_of(&story.status), 13);
         println!("{} | {} | {} | {}", id_col, name_col, desc_col, status_col);
+        println!(
+            "{}",
+            details_line(
+                story.priority,
+                &story.assignee,
+                &story.reporter,
+                story.due_date
+            )
+        );
+        println!(
+            "estimate: {}",
+            story
+                .estimate
+                .map_or("-".to_string(), |estimate| estimate.to_string())
+        );
+        println!("{}", labels_line(&story.labels));
+        println!("{}", timestamps_line(&story.created_at, &story.updated_at));
 
         println!();
+        println!("---------------------------- SUBTASKS -----------------------------");
+        if story.subtasks.is_empty() {
+            println!("no subtasks yet");
+        }
+        for (index, subtask) in story.subtasks.iter().enumerate() {
+            let check = if subtask.done { "x" } else { " " };
+            println!("{}. [{}] {}", index + 1, check, subtask.name);
+        }
+
         println!();
+        print_links(&db_state, self.db.workflows(), self.story_id);
 
-        println!("[p] previous | [u] update story | [d] delete story");
+        let comments = db_state
+            .comments
+            .get(&self.story_id)
+            .map_or(&[][..], |comments| comments.as_slice());
+        println!();
+        println!("---------------------------- COMMENTS -----------------------------");
+        if comments.is_empty() {
+            println!("no comments yet");
+        }
+        for comment in comments {
+            println!("{}", comment_header(comment));
+            println!("  {}", comment.body);
+        }
+
+        println!();
+        println!();
+
+        println!("[p] previous | [e] edit story | [u] update story | [a] assign story | [t] estimate story | [l] add label | [x] remove label | [s] add subtask | [k] check subtask | [w] remove subtask | [o] reorder subtasks | [n] link | [b] unlink | [c] add comment | [v] edit comment | [r] delete comment | [d] delete story | [m] move story | [z] undo | [y] redo");
 
         Ok(())
     }
@@ -173,8 +360,25 @@ impl Page for StoryDetail {
 
         match input {
             "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "e" => Ok(Some(Action::UpdateStory { story_id })),
             "u" => Ok(Some(Action::UpdateStoryStatus { story_id })),
+            "a" => Ok(Some(Action::AssignStory { story_id })),
+            "t" => Ok(Some(Action::EstimateStory { story_id })),
+            "l" => Ok(Some(Action::AddStoryLabel { story_id })),
+            "x" => Ok(Some(Action::RemoveStoryLabel { story_id })),
+            "s" => Ok(Some(Action::AddSubtask { story_id })),
+            "k" => Ok(Some(Action::ToggleSubtask { story_id })),
+            "w" => Ok(Some(Action::RemoveSubtask { story_id })),
+            "o" => Ok(Some(Action::MoveSubtask { story_id })),
+            "n" => Ok(Some(Action::LinkStory { story_id })),
+            "b" => Ok(Some(Action::UnlinkStory { story_id })),
+            "c" => Ok(Some(Action::AddComment { story_id })),
+            "v" => Ok(Some(Action::EditComment { story_id })),
+            "r" => Ok(Some(Action::DeleteComment { story_id })),
             "d" => Ok(Some(Action::DeleteStory { epic_id, story_id })),
+            "m" => Ok(Some(Action::MoveStory { epic_id, story_id })),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
             _ => Ok(None),
         }
     }
@@ -184,6 +388,383 @@ impl Page for StoryDetail {
     }
 }
 
+// the epics and stories assigned to one user
+pub struct MyWork {
+    pub user: String,
+    pub db: Rc<JiraDatabase>,
+}
+
+impl MyWork {
+    fn is_mine(&self, assignee: &Option<String>) -> bool {
+        assignee.as_ref() == Some(&self.user)
+    }
+
+    fn epics(&self, db_state: &DBState) -> Vec<u32> {
+        db_state
+            .epics
+            .iter()
+            .filter(|(_, epic)| self.is_mine(&epic.assignee))
+            .map(|(epic_id, _)| *epic_id)
+            .sorted()
+            .collect()
+    }
+
+    // (story id, epic id) pairs. stories no epic owns cannot be opened, so they are left out
+    fn stories(&self, db_state: &DBState) -> Vec<(u32, u32)> {
+        db_state
+            .epics
+            .iter()
+            .flat_map(|(epic_id, epic)| epic.stories.iter().map(move |id| (*id, *epic_id)))
+            .filter(|(story_id, _)| {
+                db_state
+                    .stories
+                    .get(story_id)
+                    .is_some_and(|story| self.is_mine(&story.assignee))
+            })
+            .sorted()
+            .dedup_by(|a, b| a.0 == b.0)
+            .collect()
+    }
+}
+
+impl Page for MyWork {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+        let workflows = self.db.workflows();
+
+        println!("---------------------------- MY WORK -----------------------------");
+        println!("assigned to {}", self.user);
+        println!();
+
+        println!("----------------------------- EPICS ------------------------------");
+        println!("     id     |               name               |      status      ");
+
+        for epic_id in self.epics(&db_state) {
+            let epic = &db_state.epics[&epic_id];
+            let id_col = get_column_string(&epic_id.to_string(), 11);
+            let name_col = get_column_string(&epic.name, 32);
+            let status_col = get_column_string(&workflows.epic.name_of(&epic.status), 17);
+            println!("{} | {} | {}", id_col, name_col, status_col);
+        }
+
+        println!();
+
+        println!("---------------------------- STORIES -----------------------------");
+        println!("     id     |               name               |      status      ");
+
+        for (story_id, _) in self.stories(&db_state) {
+            let story = &db_state.stories[&story_id];
+            let id_col = get_column_string(&story_id.to_string(), 11);
+            let name_col = get_column_string(&story.name, 32);
+            let status_col = get_column_string(&workflows.story.name_of(&story.status), 17);
+            println!("{} | {} | {}", id_col, name_col, status_col);
+        }
+
+        println!();
+        println!();
+
+        println!("[p] previous | [:id:] navigate to epic or story | [z] undo | [y] redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        match input {
+            "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            input => {
+                let id = match input.parse::<u32>() {
+                    Ok(id) => id,
+                    Err(_) => return Ok(None),
+                };
+                let db_state = self.db.read_db()?;
+
+                if self.epics(&db_state).contains(&id) {
+                    return Ok(Some(Action::NavigateToEpicDetail { epic_id: id }));
+                }
+                Ok(self
+                    .stories(&db_state)
+                    .into_iter()
+                    .find(|(story_id, _)| *story_id == id)
+                    .map(|(story_id, epic_id)| Action::NavigateToStoryDetail { epic_id, story_id }))
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// stories across every epic that are overdue or due within DUE_SOON_DAYS, soonest first
+pub struct DueDates {
+    pub db: Rc<JiraDatabase>,
+}
+
+impl DueDates {
+    // (story id, epic id) pairs. stories no epic owns cannot be opened, so they are left out
+    fn stories(&self, db_state: &DBState) -> Vec<(u32, u32)> {
+        let workflow = &self.db.workflows().story;
+        let today = self.db.today();
+
+        db_state
+            .epics
+            .iter()
+            .flat_map(|(epic_id, epic)| epic.stories.iter().map(move |id| (*id, *epic_id)))
+            .filter_map(|(story_id, epic_id)| {
+                let story = db_state.stories.get(&story_id)?;
+                let done = is_done(workflow, &story.status);
+                match due_state(story.due_date, done, today)? {
+                    Due::Later => None,
+                    _ => Some((story.due_date, story_id, epic_id)),
+                }
+            })
+            .sorted()
+            .dedup_by(|a, b| a.1 == b.1)
+            .map(|(_, story_id, epic_id)| (story_id, epic_id))
+            .collect()
+    }
+}
+
+impl Page for DueDates {
+    fn draw_page(&self) -> Result<()> {
+        let db_state = self.db.read_db()?;
+        let workflow = &self.db.workflows().story;
+        let today = self.db.today();
+
+        println!("--------------------- OVERDUE & DUE SOON ----------------------");
+        println!("overdue or due by {}", due_soon_horizon(today));
+        println!();
+        println!("  id   |        name        | epic  |    status    |    due    ");
+
+        for (story_id, epic_id) in self.stories(&db_state) {
+            let story = &db_state.stories[&story_id];
+            let row = format!(
+                "{} | {} | {} | {} | {}",
+                get_column_string(&story_id.to_string(), 6),
+                get_column_string(&story.name, 18),
+                get_column_string(&epic_id.to_string(), 5),
+                get_column_string(&workflow.name_of(&story.status), 12),
+                get_column_string(
+                    &story
+                        .due_date
+                        .map_or(String::new(), |date| date.to_string()),
+                    10
+                ),
+            );
+            let done = is_done(workflow, &story.status);
+            println!(
+                "{}",
+                highlight_overdue(row, due_state(story.due_date, done, today))
+            );
+        }
+
+        println!();
+        println!();
+
+        println!("[p] previous | [:id:] navigate to story | [z] undo | [y] redo");
+
+        Ok(())
+    }
+
+    fn handle_input(&self, input: &str) -> Result<Option<Action>> {
+        match input {
+            "p" => Ok(Some(Action::NavigateToPreviousPage)),
+            "z" => Ok(Some(Action::Undo)),
+            "y" => Ok(Some(Action::Redo)),
+            input => {
+                let id = match input.parse::<u32>() {
+                    Ok(id) => id,
+                    Err(_) => return Ok(None),
+                };
+
+                Ok(self
+                    .stories(&self.db.read_db()?)
+                    .into_iter()
+                    .find(|(story_id, _)| *story_id == id)
+                    .map(|(story_id, epic_id)| Action::NavigateToStoryDetail { epic_id, story_id }))
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+const ITEM_HEADER: &str =
+    "  id   |        name        |  assignee  | priority |    status    |    due    ";
+
+// one line of the epic and story tables, laid out under ITEM_HEADER
+fn item_row(
+    id: u32,
+    name: &str,
+    assignee: &Option<String>,
+    priority: Priority,
+    status: &str,
+    due_date: Option<NaiveDate>,
+) -> String {
+    item_cells(
+        &id.to_string(),
+        name,
+        assignee.as_deref().unwrap_or(""),
+        &priority.to_string(),
+        status,
+        &due_date.map_or(String::new(), |date| date.to_string()),
+    )
+}
+
+// also lays out rows for items that are missing, so they line up with the rest
+fn item_cells(
+    id: &str,
+    name: &str,
+    assignee: &str,
+    priority: &str,
+    status: &str,
+    due_date: &str,
+) -> String {
+    format!(
+        "{} | {} | {} | {} | {} | {}",
+        get_column_string(id, 6),
+        get_column_string(name, 18),
+        get_column_string(assignee, 10),
+        get_column_string(priority, 8),
+        get_column_string(status, 12),
+        get_column_string(due_date, 10),
+    )
+}
+
+// a story row on the epic page, with the subtasks column added to ITEM_HEADER
+fn epic_story_row(row: String, subtasks: &str) -> String {
+    format!("{} | {}", row, get_column_string(subtasks, 8))
+}
+
+fn details_line(
+    priority: Priority,
+    assignee: &Option<String>,
+    reporter: &Option<String>,
+    due_date: Option<NaiveDate>,
+) -> String {
+    format!(
+        "priority: {} | assignee: {} | reporter: {} | due: {}",
+        priority,
+        assignee.as_deref().unwrap_or("-"),
+        reporter.as_deref().unwrap_or("-"),
+        due_date.map_or("-".to_string(), |date| date.to_string())
+    )
+}
+
+fn is_done(workflow: &Workflow, status: &Status) -> bool {
+    workflow.category_of(status) == Some(Category::Done)
+}
+
+fn labels_line(labels: &[String]) -> String {
+    if labels.is_empty() {
+        "labels: -".to_string()
+    } else {
+        format!("labels: {}", labels.join(", "))
+    }
+}
+
+// shown in local time, items written before timestamps existed show "-"
+fn timestamps_line(
+    created_at: &Option<DateTime<Utc>>,
+    updated_at: &Option<DateTime<Utc>>,
+) -> String {
+    let format = |time: &Option<DateTime<Utc>>| time.map_or("-".to_string(), local_time);
+
+    format!(
+        "created: {} | updated: {}",
+        format(created_at),
+        format(updated_at)
+    )
+}
+
+// the links going out of an epic or story, then the ones pointing at it
+fn print_links(db_state: &DBState, workflows: &Workflows, id: u32) {
+    println!("------------------------------ LINKS ------------------------------");
+    let outbound = db_state
+        .links
+        .get(&id)
+        .into_iter()
+        .flatten()
+        .map(|link| (link.kind.outbound(), link.to));
+    let inbound = links::inbound(&db_state.links, id)
+        .into_iter()
+        .map(|(from, kind)| (kind.inbound(), from));
+    let lines: Vec<String> = outbound
+        .chain(inbound)
+        .map(|(relation, id)| link_line(db_state, workflows, relation, id))
+        .collect();
+    if lines.is_empty() {
+        println!("no links yet");
+    }
+    lines.iter().for_each(|line| println!("{line}"));
+}
+
+// e.g. "is blocked by story 3: Parser (IN PROGRESS)"
+fn link_line(db_state: &DBState, workflows: &Workflows, relation: &str, id: u32) -> String {
+    match (db_state.epics.get(&id), db_state.stories.get(&id)) {
+        (Some(epic), _) => format!(
+            "{relation} epic {id}: {} ({})",
+            epic.name,
+            workflows.epic.name_of(&epic.status)
+        ),
+        (None, Some(story)) => format!(
+            "{relation} story {id}: {} ({})",
+            story.name,
+            workflows.story.name_of(&story.status)
+        ),
+        (None, None) => format!("{relation} {id}: <missing item>"),
+    }
+}
+
+// how many of a story's subtasks are done, e.g. "3/5"
+fn subtask_progress(subtasks: &[Subtask]) -> String {
+    if subtasks.is_empty() {
+        return "-".to_string();
+    }
+
+    let done = subtasks.iter().filter(|subtask| subtask.done).count();
+    format!("{}/{}", done, subtasks.len())
+}
+
+fn local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn comment_header(comment: &Comment) -> String {
+    let edited = if comment.edited_at.is_some() {
+        " (edited)"
+    } else {
+        ""
+    };
+
+    format!(
+        "#{} {} {}{}",
+        comment.id,
+        comment.author,
+        local_time(comment.created_at),
+        edited
+    )
+}
+
+fn print_filter(filter: &RefCell<Option<String>>) {
+    if let Some(label) = filter.borrow().as_ref() {
+        println!("label: {label}");
+    }
+}
+
+fn matches_filter(filter: &RefCell<Option<String>>, labels: &[String]) -> bool {
+    filter
+        .borrow()
+        .as_ref()
+        .is_none_or(|label| labels.contains(label))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,34 +777,36 @@ mod tests {
         #[test]
         fn draw_page_should_not_throw_error() {
             let page = HomePage {
-                db: Rc::new(JiraDatabase {
-                    database: Box::new(MockDB::new()),
-                }),
+                db: Rc::new(JiraDatabase::new(Box::new(MockDB::new()))),
+                sort: Cell::default(),
+                filter: RefCell::default(),
             };
 
-            assert_eq!(page.draw_page().is_ok(), true);
+            assert!(page.draw_page().is_ok());
         }
 
         #[test]
         fn handle_input_should_not_throw_error() {
             let page = HomePage {
-                db: Rc::new(JiraDatabase {
-                    database: Box::new(MockDB::new()),
-                }),
+                db: Rc::new(JiraDatabase::new(Box::new(MockDB::new()))),
+                sort: Cell::default(),
+                filter: RefCell::default(),
             };
 
-            assert_eq!(page.handle_input("").is_ok(), true);
+            assert!(page.handle_input("").is_ok());
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
             let epic_id = db
                 .create_epic(Epic::new("".to_string(), "".to_string()))
                 .unwrap();
-            let page = HomePage { db };
+            let page = HomePage {
+                db,
+                sort: Cell::default(),
+                filter: RefCell::default(),
+            };
 
             let q = "q";
             let c = "c";
@@ -235,10 +818,15 @@ mod tests {
 
             assert_eq!(page.handle_input(q).unwrap(), Some(Action::Exit));
             assert_eq!(page.handle_input(c).unwrap(), Some(Action::CreateEpic));
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
             assert_eq!(
                 page.handle_input(&valid_epic_id).unwrap(),
                 Some(Action::NavigateToEpicDetail { epic_id: 1 })
             );
+            assert_eq!(page.handle_input("s").unwrap(), None);
+            assert_eq!(page.sort.get(), SortKey::Priority);
+            assert_eq!(page.handle_input("f").unwrap(), Some(Action::FilterByLabel));
             assert_eq!(page.handle_input(invalid_epic_id).unwrap(), None);
             assert_eq!(page.handle_input(junck_input).unwrap(), None);
             assert_eq!(
@@ -260,56 +848,169 @@ mod tests {
             let invalid_epic_id = 999;
             let page = EpicDetail {
                 epic_id: invalid_epic_id,
-                db: Rc::new(JiraDatabase {
-                    database: Box::new(MockDB::new()),
-                }),
+                db: Rc::new(JiraDatabase::new(Box::new(MockDB::new()))),
+                sort: Cell::default(),
+                filter: RefCell::default(),
             };
 
-            assert_eq!(page.draw_page().is_err(), true);
+            let error = page.draw_page().unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<JiraError>(),
+                Some(JiraError::EpicNotFound(999))
+            ));
         }
 
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
             let page = EpicDetail {
                 epic_id: db
                     .create_epic(Epic::new("".to_string(), "".to_string()))
                     .unwrap(),
                 db,
+                sort: Cell::default(),
+                filter: RefCell::default(),
+            };
+
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn draw_page_should_survive_dangling_story_id() {
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+            let epic_id = db
+                .create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+            let mut db_state = db.read_db().unwrap();
+            db_state.epics.get_mut(&epic_id).unwrap().stories.push(999);
+            db.database.write_db(&db_state).unwrap();
+            let page = EpicDetail {
+                epic_id,
+                db,
+                sort: Cell::default(),
+                filter: RefCell::default(),
+            };
+
+            assert!(page.draw_page().is_ok());
+        }
+
+        #[test]
+        fn draw_page_should_sort_by_every_key() {
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+            let epic_id = db
+                .create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+            for name in ["b", "a"] {
+                db.create_story(Story::new(name.to_string(), "".to_string()), epic_id)
+                    .unwrap();
+            }
+            let page = EpicDetail {
+                epic_id,
+                db,
+                sort: Cell::default(),
+                filter: RefCell::default(),
             };
 
-            assert_eq!(page.draw_page().is_ok(), true);
+            for _ in 0..4 {
+                assert!(page.draw_page().is_ok());
+                assert_eq!(page.handle_input("s").unwrap(), None);
+            }
+            assert_eq!(page.sort.get(), SortKey::Id);
+        }
+
+        #[test]
+        fn draw_page_should_filter_stories_by_label() {
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+            let epic_id = db
+                .create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap();
+            db.add_story_label(story_id, "backend").unwrap();
+            let page = EpicDetail {
+                epic_id,
+                db,
+                sort: Cell::default(),
+                filter: RefCell::new(Some("frontend".to_string())),
+            };
+
+            assert!(page.draw_page().is_ok());
+            assert!(!matches_filter(&page.filter, &[]));
+            *page.filter.borrow_mut() = Some("backend".to_string());
+            assert!(matches_filter(&page.filter, &["backend".to_string()]));
+            assert_eq!(page.handle_input("f").unwrap(), Some(Action::FilterByLabel));
         }
 
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
             let page = EpicDetail {
                 epic_id: db
                     .create_epic(Epic::new("".to_string(), "".to_string()))
                     .unwrap(),
                 db,
+                sort: Cell::default(),
+                filter: RefCell::default(),
             };
 
-            assert_eq!(page.handle_input("").is_ok(), true);
+            assert!(page.handle_input("").is_ok());
+        }
+
+        #[test]
+        fn handle_input_should_not_open_stories_moved_to_another_epic() {
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+            let from_epic_id = db
+                .create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+            let to_epic_id = db
+                .create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_string(), "".to_string()), from_epic_id)
+                .unwrap();
+            db.move_story(story_id, from_epic_id, to_epic_id).unwrap();
+            let page = EpicDetail {
+                epic_id: from_epic_id,
+                db,
+                sort: Cell::default(),
+                filter: RefCell::default(),
+            };
+
+            assert_eq!(page.handle_input(&story_id.to_string()).unwrap(), None);
+        }
+
+        #[test]
+        fn handle_input_should_throw_error_for_invalid_epic_id() {
+            let page = EpicDetail {
+                epic_id: 999,
+                db: Rc::new(JiraDatabase::new(Box::new(MockDB::new()))),
+                sort: Cell::default(),
+                filter: RefCell::default(),
+            };
+
+            let error = page.handle_input("p").unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<JiraError>(),
+                Some(JiraError::EpicNotFound(999))
+            ));
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
             let epic_id = db
                 .create_epic(Epic::new("".to_string(), "".to_string()))
                 .unwrap();
             let story_id = db
                 .create_story(Story::new("".to_string(), "".to_string()), epic_id)
                 .unwrap();
-            let page = EpicDetail { epic_id, db };
+            let page = EpicDetail {
+                epic_id,
+                db,
+                sort: Cell::default(),
+                filter: RefCell::default(),
+            };
 
             let p = "p";
             let u = "u";
@@ -329,10 +1030,32 @@ mod tests {
                 page.handle_input(u).unwrap(),
                 Some(Action::UpdateEpicStatus { epic_id })
             );
+            assert_eq!(
+                page.handle_input("e").unwrap(),
+                Some(Action::UpdateEpic { epic_id })
+            );
             assert_eq!(
                 page.handle_input(d).unwrap(),
                 Some(Action::DeleteEpic { epic_id })
             );
+            assert_eq!(
+                page.handle_input("l").unwrap(),
+                Some(Action::AddEpicLabel { epic_id })
+            );
+            assert_eq!(
+                page.handle_input("x").unwrap(),
+                Some(Action::RemoveEpicLabel { epic_id })
+            );
+            assert_eq!(
+                page.handle_input("n").unwrap(),
+                Some(Action::LinkEpic { epic_id })
+            );
+            assert_eq!(
+                page.handle_input("b").unwrap(),
+                Some(Action::UnlinkEpic { epic_id })
+            );
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
             assert_eq!(
                 page.handle_input(c).unwrap(),
                 Some(Action::CreateStory { epic_id })
@@ -365,9 +1088,7 @@ mod tests {
         #[test]
         fn draw_page_should_throw_error_for_invalid_story_id() {
             let invalid_story_id = 999;
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
 
             let epic_id = db
                 .create_epic(Epic::new("".to_owned(), "".to_owned()))
@@ -381,14 +1102,16 @@ mod tests {
                 story_id: invalid_story_id,
                 db,
             };
-            assert_eq!(page.draw_page().is_err(), true);
+            let error = page.draw_page().unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<JiraError>(),
+                Some(JiraError::StoryNotFound(999))
+            ));
         }
 
         #[test]
         fn draw_page_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
             let epic_id = db
                 .create_epic(Epic::new("".to_string(), "".to_string()))
                 .unwrap();
@@ -400,14 +1123,12 @@ mod tests {
                 story_id,
                 db,
             };
-            assert_eq!(page.draw_page().is_ok(), true);
+            assert!(page.draw_page().is_ok());
         }
 
         #[test]
         fn handle_input_should_not_throw_error() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
             let epic_id = db
                 .create_epic(Epic::new("".to_string(), "".to_string()))
                 .unwrap();
@@ -421,14 +1142,12 @@ mod tests {
                 db,
             };
 
-            assert_eq!(page.handle_input("").is_ok(), true);
+            assert!(page.handle_input("").is_ok());
         }
 
         #[test]
         fn handle_input_should_return_the_correct_actions() {
-            let db = Rc::new(JiraDatabase {
-                database: Box::new(MockDB::new()),
-            });
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
             let epic_id = db
                 .create_epic(Epic::new("".to_string(), "".to_string()))
                 .unwrap();
@@ -457,10 +1176,68 @@ mod tests {
                 page.handle_input(u).unwrap(),
                 Some(Action::UpdateStoryStatus { story_id })
             );
+            assert_eq!(
+                page.handle_input("e").unwrap(),
+                Some(Action::UpdateStory { story_id })
+            );
+            assert_eq!(
+                page.handle_input("m").unwrap(),
+                Some(Action::MoveStory { epic_id, story_id })
+            );
+            assert_eq!(
+                page.handle_input("l").unwrap(),
+                Some(Action::AddStoryLabel { story_id })
+            );
+            assert_eq!(
+                page.handle_input("t").unwrap(),
+                Some(Action::EstimateStory { story_id })
+            );
+            assert_eq!(
+                page.handle_input("x").unwrap(),
+                Some(Action::RemoveStoryLabel { story_id })
+            );
+            assert_eq!(
+                page.handle_input("s").unwrap(),
+                Some(Action::AddSubtask { story_id })
+            );
+            assert_eq!(
+                page.handle_input("k").unwrap(),
+                Some(Action::ToggleSubtask { story_id })
+            );
+            assert_eq!(
+                page.handle_input("w").unwrap(),
+                Some(Action::RemoveSubtask { story_id })
+            );
+            assert_eq!(
+                page.handle_input("o").unwrap(),
+                Some(Action::MoveSubtask { story_id })
+            );
+            assert_eq!(
+                page.handle_input("n").unwrap(),
+                Some(Action::LinkStory { story_id })
+            );
+            assert_eq!(
+                page.handle_input("b").unwrap(),
+                Some(Action::UnlinkStory { story_id })
+            );
+            assert_eq!(
+                page.handle_input("c").unwrap(),
+                Some(Action::AddComment { story_id })
+            );
+            assert_eq!(
+                page.handle_input("v").unwrap(),
+                Some(Action::EditComment { story_id })
+            );
+            assert_eq!(
+                page.handle_input("r").unwrap(),
+                Some(Action::DeleteComment { story_id })
+            );
             assert_eq!(
                 page.handle_input(d).unwrap(),
                 Some(Action::DeleteStory { epic_id, story_id })
             );
+            assert_eq!(page.handle_input("z").unwrap(), Some(Action::Undo));
+            assert_eq!(page.handle_input("y").unwrap(), Some(Action::Redo));
             assert_eq!(page.handle_input(some_number).unwrap(), None);
             assert_eq!(page.handle_input(junk_input).unwrap(), None);
             assert_eq!(
@@ -473,4 +1250,173 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn timestamps_line_should_show_missing_times_as_dash() {
+        assert_eq!(timestamps_line(&None, &None), "created: - | updated: -");
+        assert!(timestamps_line(&Some(Utc::now()), &None).ends_with("| updated: -"));
+    }
+
+    #[test]
+    fn link_line_should_name_the_linked_item() {
+        let db = JiraDatabase::new(Box::new(MockDB::new()));
+        let epic_id = db
+            .create_epic(Epic::new("Parser".to_string(), "".to_string()))
+            .unwrap();
+        let story_id = db
+            .create_story(Story::new("Lexer".to_string(), "".to_string()), epic_id)
+            .unwrap();
+        let db_state = db.read_db().unwrap();
+        let workflows = db.workflows();
+
+        assert_eq!(
+            link_line(&db_state, workflows, "blocks", epic_id),
+            format!("blocks epic {epic_id}: Parser (OPEN)")
+        );
+        assert_eq!(
+            link_line(&db_state, workflows, "is blocked by", story_id),
+            format!("is blocked by story {story_id}: Lexer (OPEN)")
+        );
+        assert_eq!(
+            link_line(&db_state, workflows, "relates to", 99),
+            "relates to 99: <missing item>"
+        );
+    }
+
+    #[test]
+    fn epic_story_row_should_line_up_missing_stories_with_known_ones() {
+        let known = epic_story_row(
+            item_row(1, "name", &None, Priority::Low, "OPEN", None),
+            "1/2",
+        );
+        let missing = epic_story_row(item_cells("2", "<missing story>", "", "", "?", ""), "");
+
+        assert_eq!(known.len(), missing.len());
+        assert_eq!(known.matches('|').count(), 6);
+        assert_eq!(missing.matches('|').count(), 6);
+    }
+
+    #[test]
+    fn subtask_progress_should_count_done_subtasks() {
+        let mut subtasks = vec![
+            Subtask::new("a".to_string()),
+            Subtask::new("b".to_string()),
+            Subtask::new("c".to_string()),
+        ];
+        subtasks[1].done = true;
+
+        assert_eq!(subtask_progress(&subtasks), "1/3");
+        assert_eq!(subtask_progress(&[]), "-");
+    }
+
+    #[test]
+    fn comment_header_should_mark_edited_comments() {
+        let mut comment = Comment {
+            id: 3,
+            author: "jdoe".to_string(),
+            created_at: Utc::now(),
+            body: "".to_string(),
+            edited_at: None,
+        };
+        assert!(comment_header(&comment).starts_with("#3 jdoe "));
+        assert!(!comment_header(&comment).ends_with("(edited)"));
+
+        comment.edited_at = Some(Utc::now());
+        assert!(comment_header(&comment).ends_with(" (edited)"));
+    }
+
+    mod due_dates_page {
+        use chrono::{Duration, TimeZone, Utc};
+
+        use super::*;
+        use crate::clock::FixedClock;
+
+        #[test]
+        fn handle_input_should_open_overdue_and_due_soon_stories_only() {
+            let clock = Rc::new(FixedClock::new(
+                Utc.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap(),
+            ));
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())).with_clock(clock));
+            let today = db.today();
+            let epic_id = db
+                .create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+            let mut ids = vec![];
+            for days_ahead in [None, Some(-1), Some(3), Some(30)] {
+                let mut story = Story::new("".to_string(), "".to_string());
+                story.due_date = days_ahead.map(|days| today + Duration::days(days));
+                ids.push(db.create_story(story, epic_id).unwrap());
+            }
+            let done_id = db
+                .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap();
+            db.update_story(
+                done_id,
+                "".to_string(),
+                "".to_string(),
+                Priority::Medium,
+                Some(today - Duration::days(5)),
+            )
+            .unwrap();
+            db.update_story_status(done_id, Status::new("closed"))
+                .unwrap();
+            let page = DueDates { db };
+
+            assert!(page.draw_page().is_ok());
+            assert_eq!(
+                page.stories(&page.db.read_db().unwrap()),
+                vec![(ids[1], epic_id), (ids[2], epic_id)]
+            );
+            assert_eq!(
+                page.handle_input(&ids[1].to_string()).unwrap(),
+                Some(Action::NavigateToStoryDetail {
+                    epic_id,
+                    story_id: ids[1]
+                })
+            );
+            assert_eq!(page.handle_input(&ids[3].to_string()).unwrap(), None);
+            assert_eq!(page.handle_input(&done_id.to_string()).unwrap(), None);
+        }
+    }
+
+    mod my_work_page {
+        use super::*;
+
+        #[test]
+        fn handle_input_should_open_assigned_items_only() {
+            let db = Rc::new(JiraDatabase::new(Box::new(MockDB::new())));
+            db.add_user("jdoe".to_string(), "Jane Doe".to_string())
+                .unwrap();
+            let epic_id = db
+                .create_epic(Epic::new("".to_string(), "".to_string()))
+                .unwrap();
+            let story_id = db
+                .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap();
+            let other_story_id = db
+                .create_story(Story::new("".to_string(), "".to_string()), epic_id)
+                .unwrap();
+            db.assign_story(story_id, Some("jdoe".to_string()), None)
+                .unwrap();
+            let page = MyWork {
+                user: "jdoe".to_string(),
+                db,
+            };
+
+            assert!(page.draw_page().is_ok());
+            assert_eq!(
+                page.handle_input(&story_id.to_string()).unwrap(),
+                Some(Action::NavigateToStoryDetail { epic_id, story_id })
+            );
+            assert_eq!(page.handle_input(&epic_id.to_string()).unwrap(), None);
+            assert_eq!(
+                page.handle_input(&other_story_id.to_string()).unwrap(),
+                None
+            );
+            assert_eq!(
+                page.handle_input("p").unwrap(),
+                Some(Action::NavigateToPreviousPage)
+            );
+        }
+    }
 }
@@ -1,7 +1,99 @@
-use std::cmp::Ordering::{Equal, Greater, Less};
+use std::cmp::Ordering::{self, Equal, Greater, Less};
 
+use chrono::{Days, NaiveDate};
 use ellipse::Ellipse;
 
+use crate::models::Priority;
+
+// how far ahead the due dates page looks
+pub const DUE_SOON_DAYS: u64 = 7;
+
+// what the epic and story tables are ordered by, `s` moves on to the next one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    #[default]
+    Id,
+    Priority,
+    Status,
+    Name,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Id => SortKey::Priority,
+            SortKey::Priority => SortKey::Status,
+            SortKey::Status => SortKey::Name,
+            SortKey::Name => SortKey::Id,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Id => "id",
+            SortKey::Priority => "priority",
+            SortKey::Status => "status",
+            SortKey::Name => "name",
+        }
+    }
+}
+
+// the parts of a table row it can be sorted by. `status` is the state's position in its workflow
+pub struct SortFields<'a> {
+    pub id: u32,
+    pub name: &'a str,
+    pub priority: Priority,
+    pub status: usize,
+}
+
+// rows that tie are kept in id order
+pub fn compare(sort: SortKey, a: &SortFields, b: &SortFields) -> Ordering {
+    let order = match sort {
+        SortKey::Id => Equal,
+        SortKey::Priority => a.priority.cmp(&b.priority),
+        SortKey::Status => a.status.cmp(&b.status),
+        SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+    };
+
+    order.then(a.id.cmp(&b.id))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Due {
+    Overdue,
+    Soon,
+    Later,
+}
+
+// the last day the due dates page counts as due soon
+pub fn due_soon_horizon(today: NaiveDate) -> NaiveDate {
+    today
+        .checked_add_days(Days::new(DUE_SOON_DAYS))
+        .unwrap_or(NaiveDate::MAX)
+}
+
+// items without a due date or that are already done have nothing due
+pub fn due_state(due_date: Option<NaiveDate>, done: bool, today: NaiveDate) -> Option<Due> {
+    let due_date = due_date.filter(|_| !done)?;
+    let soon = due_soon_horizon(today);
+
+    Some(if due_date < today {
+        Due::Overdue
+    } else if due_date <= soon {
+        Due::Soon
+    } else {
+        Due::Later
+    })
+}
+
+// overdue rows are printed in red
+pub fn highlight_overdue(row: String, due: Option<Due>) -> String {
+    match due {
+        Some(Due::Overdue) => format!("\x1b[31m{row}\x1b[0m"),
+        _ => row,
+    }
+}
+
 pub fn get_column_string(text: &str, width: usize) -> String {
     let len = text.len();
 
@@ -10,7 +102,7 @@ pub fn get_column_string(text: &str, width: usize) -> String {
         Less => {
             let left_over = width - len;
             let mut column_string = text.to_string();
-            let padding = std::iter::repeat(' ').take(left_over).collect::<String>();
+            let padding = std::iter::repeat_n(' ', left_over).collect::<String>();
 
             column_string.push_str(&padding);
 
@@ -39,6 +131,84 @@ pub fn get_column_string(text: &str, width: usize) -> String {
 mod test {
     use super::*;
 
+    #[test]
+    fn compare_should_break_ties_by_id() {
+        let row = |id, name, priority, status| SortFields {
+            id,
+            name,
+            priority,
+            status,
+        };
+        let mut rows = [
+            row(1, "beta", Priority::Low, 2),
+            row(2, "Alpha", Priority::High, 0),
+            row(3, "alpha", Priority::Low, 0),
+        ];
+        let ids = |rows: &[SortFields]| rows.iter().map(|row| row.id).collect::<Vec<_>>();
+
+        rows.sort_by(|a, b| compare(SortKey::Priority, a, b));
+        assert_eq!(ids(&rows), vec![2, 1, 3]);
+
+        rows.sort_by(|a, b| compare(SortKey::Status, a, b));
+        assert_eq!(ids(&rows), vec![2, 3, 1]);
+
+        rows.sort_by(|a, b| compare(SortKey::Name, a, b));
+        assert_eq!(ids(&rows), vec![2, 3, 1]);
+
+        rows.sort_by(|a, b| compare(SortKey::Id, a, b));
+        assert_eq!(ids(&rows), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn sort_key_should_cycle() {
+        let mut sort = SortKey::default();
+        let mut seen = vec![];
+        for _ in 0..4 {
+            seen.push(sort.label());
+            sort = sort.next();
+        }
+
+        assert_eq!(seen, vec!["id", "priority", "status", "name"]);
+        assert_eq!(sort, SortKey::Id);
+    }
+
+    #[test]
+    fn due_state_should_ignore_done_items() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let day = |day| NaiveDate::from_ymd_opt(2024, 1, day);
+
+        assert_eq!(due_state(day(9), false, today), Some(Due::Overdue));
+        assert_eq!(due_state(day(9), true, today), None);
+        assert_eq!(due_state(day(10), false, today), Some(Due::Soon));
+        assert_eq!(due_state(day(17), false, today), Some(Due::Soon));
+        assert_eq!(due_state(day(18), false, today), Some(Due::Later));
+        assert_eq!(due_state(None, false, today), None);
+    }
+
+    #[test]
+    fn due_soon_horizon_should_not_overflow() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+
+        assert_eq!(
+            due_soon_horizon(today),
+            NaiveDate::from_ymd_opt(2024, 1, 17).unwrap()
+        );
+        assert_eq!(due_soon_horizon(NaiveDate::MAX), NaiveDate::MAX);
+        assert_eq!(
+            due_state(Some(NaiveDate::MAX), false, NaiveDate::MAX),
+            Some(Due::Soon)
+        );
+    }
+
+    #[test]
+    fn highlight_overdue_should_only_color_overdue_rows() {
+        assert_eq!(
+            highlight_overdue("row".to_string(), Some(Due::Overdue)),
+            "\x1b[31mrow\x1b[0m"
+        );
+        assert_eq!(highlight_overdue("row".to_string(), Some(Due::Soon)), "row");
+    }
+
     #[test]
     fn test_get_column_string() {
         let text1 = "";
@@ -1,14 +1,59 @@
+use chrono::NaiveDate;
+use itertools::Itertools;
+
 use crate::{
     io_utils::get_user_input,
-    models::{Epic, Status, Story},
+    models::{Comment, Epic, LinkKind, Priority, Status, Story, Subtask},
+    workflow::State,
 };
 
+// offered only the states the workflow allows moving to
+pub type StatusPrompt = Box<dyn Fn(&[&State]) -> Option<Status>>;
+// offered the points of the board's scale and the current estimate, returns the new one
+pub type EstimatePrompt = Box<dyn Fn(&[u32], Option<u32>) -> Option<u32>>;
+// offered the current user's comments, returns which one to change and its new text
+pub type EditCommentPrompt = Box<dyn Fn(&[Comment]) -> Option<(u32, String)>>;
+pub type DeleteCommentPrompt = Box<dyn Fn(&[Comment]) -> Option<u32>>;
+// offered a story's subtasks, returns the index of the one picked
+pub type SubtaskPrompt = Box<dyn Fn(&[Subtask]) -> Option<usize>>;
+// returns the index of the subtask to move and the index to move it to
+pub type MoveSubtaskPrompt = Box<dyn Fn(&[Subtask]) -> Option<(usize, usize)>>;
+// given the epic or story on screen, returns the link to make as (from, kind, to)
+pub type LinkPrompt = Box<dyn Fn(u32) -> Option<(u32, LinkKind, u32)>>;
+// offered the links of the item on screen as (from, kind, to), returns the index of the one picked
+pub type UnlinkPrompt = Box<dyn Fn(&[(u32, LinkKind, u32)]) -> Option<usize>>;
+// offered the open items blocking a story that is about to be resolved, returns whether to go on
+pub type ResolveBlockedPrompt = Box<dyn Fn(&[u32]) -> bool>;
+// offered the labels an item carries
+pub type RemoveLabelPrompt = Box<dyn Fn(&[String]) -> Option<String>>;
+// offered every label with how many items carry it, `None` clears the filter
+pub type FilterPrompt = Box<dyn Fn(&[(String, usize)]) -> Option<String>>;
+
 pub struct Prompts {
     pub create_epic: Box<dyn Fn() -> Epic>,
     pub create_story: Box<dyn Fn() -> Story>,
+    pub update_epic: Box<dyn Fn(&Epic) -> Epic>,
+    pub update_story: Box<dyn Fn(&Story) -> Story>,
+    pub assign_epic: Box<dyn Fn(&Epic) -> Epic>,
+    pub assign_story: Box<dyn Fn(&Story) -> Story>,
     pub delete_epic: Box<dyn Fn() -> bool>,
     pub delete_story: Box<dyn Fn() -> bool>,
-    pub update_status: Box<dyn Fn() -> Option<Status>>,
+    pub move_story: Box<dyn Fn() -> Option<u32>>,
+    pub update_status: StatusPrompt,
+    pub estimate_story: EstimatePrompt,
+    pub add_label: Box<dyn Fn() -> Option<String>>,
+    pub remove_label: RemoveLabelPrompt,
+    pub filter_label: FilterPrompt,
+    pub add_comment: Box<dyn Fn() -> Option<String>>,
+    pub edit_comment: EditCommentPrompt,
+    pub delete_comment: DeleteCommentPrompt,
+    pub add_subtask: Box<dyn Fn() -> Option<String>>,
+    pub toggle_subtask: SubtaskPrompt,
+    pub remove_subtask: SubtaskPrompt,
+    pub move_subtask: MoveSubtaskPrompt,
+    pub link_item: LinkPrompt,
+    pub unlink_item: UnlinkPrompt,
+    pub resolve_blocked: ResolveBlockedPrompt,
 }
 
 impl Prompts {
@@ -16,9 +61,28 @@ impl Prompts {
         Self {
             create_epic: Box::new(create_epic_prompt),
             create_story: Box::new(create_story_prompt),
+            update_epic: Box::new(update_epic_prompt),
+            update_story: Box::new(update_story_prompt),
+            assign_epic: Box::new(assign_epic_prompt),
+            assign_story: Box::new(assign_story_prompt),
             delete_epic: Box::new(delete_epic_prompt),
             delete_story: Box::new(delete_story_prompt),
+            move_story: Box::new(move_story_prompt),
             update_status: Box::new(update_status_prompt),
+            estimate_story: Box::new(estimate_story_prompt),
+            add_label: Box::new(add_label_prompt),
+            remove_label: Box::new(remove_label_prompt),
+            filter_label: Box::new(filter_label_prompt),
+            add_comment: Box::new(add_comment_prompt),
+            edit_comment: Box::new(edit_comment_prompt),
+            delete_comment: Box::new(delete_comment_prompt),
+            add_subtask: Box::new(add_subtask_prompt),
+            toggle_subtask: Box::new(toggle_subtask_prompt),
+            remove_subtask: Box::new(remove_subtask_prompt),
+            move_subtask: Box::new(move_subtask_prompt),
+            link_item: Box::new(link_item_prompt),
+            unlink_item: Box::new(unlink_item_prompt),
+            resolve_blocked: Box::new(resolve_blocked_prompt),
         }
     }
 }
@@ -31,7 +95,10 @@ fn create_epic_prompt() -> Epic {
     println!("Epic Description: ");
     let epic_desc = get_user_input();
 
-    Epic::new(epic_name, epic_desc)
+    let mut epic = Epic::new(epic_name, epic_desc);
+    epic.priority = priority_prompt(epic.priority);
+    epic.due_date = due_date_prompt(epic.due_date);
+    epic
 }
 
 fn create_story_prompt() -> Story {
@@ -42,7 +109,156 @@ fn create_story_prompt() -> Story {
     println!("Story Description: ");
     let story_desc = get_user_input();
 
-    Story::new(story_name, story_desc)
+    let mut story = Story::new(story_name, story_desc);
+    story.priority = priority_prompt(story.priority);
+    story.due_date = due_date_prompt(story.due_date);
+    story
+}
+
+fn update_epic_prompt(epic: &Epic) -> Epic {
+    println!("----------------------------");
+    println!("Epic Name (empty keeps \"{}\"): ", epic.name);
+    let epic_name = get_user_input();
+
+    println!("Epic Description (empty keeps \"{}\"): ", epic.description);
+    let epic_desc = get_user_input();
+
+    let priority = priority_prompt(epic.priority);
+    let due_date = due_date_prompt(epic.due_date);
+
+    Epic {
+        name: keep_if_empty(epic_name, &epic.name),
+        description: keep_if_empty(epic_desc, &epic.description),
+        priority,
+        due_date,
+        ..epic.clone()
+    }
+}
+
+fn update_story_prompt(story: &Story) -> Story {
+    println!("----------------------------");
+    println!("Story Name (empty keeps \"{}\"): ", story.name);
+    let story_name = get_user_input();
+
+    println!(
+        "Story Description (empty keeps \"{}\"): ",
+        story.description
+    );
+    let story_desc = get_user_input();
+
+    let priority = priority_prompt(story.priority);
+    let due_date = due_date_prompt(story.due_date);
+
+    Story {
+        name: keep_if_empty(story_name, &story.name),
+        description: keep_if_empty(story_desc, &story.description),
+        priority,
+        due_date,
+        ..story.clone()
+    }
+}
+
+fn assign_epic_prompt(epic: &Epic) -> Epic {
+    let (assignee, reporter) = people_prompt(&epic.assignee, &epic.reporter);
+
+    Epic {
+        assignee,
+        reporter,
+        ..epic.clone()
+    }
+}
+
+fn assign_story_prompt(story: &Story) -> Story {
+    let (assignee, reporter) = people_prompt(&story.assignee, &story.reporter);
+
+    Story {
+        assignee,
+        reporter,
+        ..story.clone()
+    }
+}
+
+fn people_prompt(
+    assignee: &Option<String>,
+    reporter: &Option<String>,
+) -> (Option<String>, Option<String>) {
+    println!("----------------------------");
+    println!(
+        "Assignee (user id, empty keeps \"{}\", - clears): ",
+        assignee.as_deref().unwrap_or("")
+    );
+    let new_assignee = get_user_input();
+
+    println!(
+        "Reporter (user id, empty keeps \"{}\", - clears): ",
+        reporter.as_deref().unwrap_or("")
+    );
+    let new_reporter = get_user_input();
+
+    (
+        keep_or_clear(new_assignee, assignee),
+        keep_or_clear(new_reporter, reporter),
+    )
+}
+
+fn keep_or_clear(input: String, current: &Option<String>) -> Option<String> {
+    match input.as_str() {
+        "" => current.clone(),
+        "-" => None,
+        _ => Some(input),
+    }
+}
+
+// anything but one of the listed numbers keeps `current`
+fn priority_prompt(current: Priority) -> Priority {
+    let choices = Priority::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, priority)| format!("{} - {}", index + 1, priority))
+        .join(", ");
+    println!("Priority ({}, empty keeps {}): ", choices, current);
+
+    get_user_input()
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| choice.checked_sub(1))
+        .and_then(|index| Priority::ALL.get(index).copied())
+        .unwrap_or(current)
+}
+
+// asks again until the answer is a real date, empty or "-"
+fn due_date_prompt(current: Option<NaiveDate>) -> Option<NaiveDate> {
+    let current_text = current.map_or(String::new(), |date| date.to_string());
+
+    loop {
+        println!(
+            "Due Date (YYYY-MM-DD, empty keeps \"{}\", - clears): ",
+            current_text
+        );
+
+        match parse_due_date(&get_user_input(), current) {
+            Ok(due_date) => return due_date,
+            Err(error) => println!("{}", error),
+        }
+    }
+}
+
+fn parse_due_date(input: &str, current: Option<NaiveDate>) -> Result<Option<NaiveDate>, String> {
+    match input.trim() {
+        "" => Ok(current),
+        "-" => Ok(None),
+        input => NaiveDate::parse_from_str(input, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| format!("\"{input}\" is not a date like 2024-01-31")),
+    }
+}
+
+fn keep_if_empty(input: String, current: &str) -> String {
+    if input.is_empty() {
+        current.to_string()
+    } else {
+        input
+    }
 }
 
 fn delete_epic_prompt() -> bool {
@@ -59,18 +275,247 @@ fn delete_story_prompt() -> bool {
     get_user_input().eq("Y")
 }
 
-fn update_status_prompt() -> Option<Status> {
+fn move_story_prompt() -> Option<u32> {
+    println!("----------------------------");
+    println!("Move story to epic (id): ");
+
+    get_user_input().parse::<u32>().ok()
+}
+
+fn update_status_prompt(targets: &[&State]) -> Option<Status> {
     println!("----------------------------");
-    println!("New Status (1 - OPEN, 2 - IN PROGRESS, 3 - RESOLVED, 4 - CLOSED): ");
+    let choices = targets
+        .iter()
+        .enumerate()
+        .map(|(index, state)| format!("{} - {}", index + 1, state.name))
+        .join(", ");
+    println!("New Status ({}): ", choices);
 
     get_user_input()
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| choice.checked_sub(1))
+        .and_then(|index| targets.get(index))
+        .map(|state| state.id.clone())
+}
+
+// anything that is not on the scale keeps `current`
+fn estimate_story_prompt(scale: &[u32], current: Option<u32>) -> Option<u32> {
+    println!("----------------------------");
+    println!(
+        "Estimate ({}, empty keeps {}, - clears): ",
+        scale.iter().join(", "),
+        current.map_or("none".to_string(), |estimate| estimate.to_string())
+    );
+
+    let input = get_user_input();
+    if input == "-" {
+        return None;
+    }
+
+    input
         .parse::<u32>()
         .ok()
-        .and_then(|status| match status {
-            1 => Some(Status::Open),
-            2 => Some(Status::InProgress),
-            3 => Some(Status::Resolved),
-            4 => Some(Status::Closed),
-            _ => None,
-        })
+        .filter(|estimate| scale.contains(estimate))
+        .or(current)
+}
+
+fn add_subtask_prompt() -> Option<String> {
+    println!("----------------------------");
+    println!("Subtask (empty cancels): ");
+
+    Some(get_user_input()).filter(|name| !name.is_empty())
+}
+
+fn toggle_subtask_prompt(subtasks: &[Subtask]) -> Option<usize> {
+    println!("----------------------------");
+    println!("Check or Uncheck Subtask (1-{}): ", subtasks.len());
+
+    input_to_index(&get_user_input(), subtasks.len())
+}
+
+fn remove_subtask_prompt(subtasks: &[Subtask]) -> Option<usize> {
+    println!("----------------------------");
+    println!("Remove Subtask (1-{}): ", subtasks.len());
+    let index = input_to_index(&get_user_input(), subtasks.len())?;
+
+    println!(
+        "Are you sure you want to remove \"{}\"? [Y/n]: ",
+        subtasks[index].name
+    );
+    get_user_input().eq("Y").then_some(index)
+}
+
+fn move_subtask_prompt(subtasks: &[Subtask]) -> Option<(usize, usize)> {
+    println!("----------------------------");
+    println!("Move Subtask (1-{}): ", subtasks.len());
+    let from = input_to_index(&get_user_input(), subtasks.len())?;
+
+    println!("To Position (1-{}): ", subtasks.len());
+    let to = input_to_index(&get_user_input(), subtasks.len())?;
+
+    Some((from, to))
+}
+
+// lists are numbered from 1 on screen, returns the index behind the number
+fn input_to_index(input: &str, len: usize) -> Option<usize> {
+    input
+        .parse::<usize>()
+        .ok()
+        .filter(|number| (1..=len).contains(number))
+        .map(|number| number - 1)
+}
+
+fn link_item_prompt(id: u32) -> Option<(u32, LinkKind, u32)> {
+    println!("----------------------------");
+    println!("{id}...");
+    println!("1. blocks");
+    println!("2. is blocked by");
+    println!("3. relates to");
+    println!("4. duplicates");
+    let choice = get_user_input();
+
+    println!("Epic or Story Id: ");
+    let other = get_user_input().parse::<u32>().ok()?;
+
+    match choice.as_str() {
+        "1" => Some((id, LinkKind::Blocks, other)),
+        "2" => Some((other, LinkKind::Blocks, id)),
+        "3" => Some((id, LinkKind::RelatesTo, other)),
+        "4" => Some((id, LinkKind::Duplicates, other)),
+        _ => None,
+    }
+}
+
+fn unlink_item_prompt(links: &[(u32, LinkKind, u32)]) -> Option<usize> {
+    println!("----------------------------");
+    for (index, (from, kind, to)) in links.iter().enumerate() {
+        println!("{}. {} {} {}", index + 1, from, kind.outbound(), to);
+    }
+    println!("Remove Link (1-{}): ", links.len());
+
+    input_to_index(&get_user_input(), links.len())
+}
+
+fn resolve_blocked_prompt(blockers: &[u32]) -> bool {
+    println!("----------------------------");
+    println!(
+        "This story is still blocked by {}. Resolve it anyway? [Y/n]: ",
+        blockers.iter().join(", ")
+    );
+
+    get_user_input().eq("Y")
+}
+
+fn add_comment_prompt() -> Option<String> {
+    println!("----------------------------");
+    println!("Comment (empty cancels): ");
+
+    Some(get_user_input()).filter(|body| !body.is_empty())
+}
+
+fn edit_comment_prompt(comments: &[Comment]) -> Option<(u32, String)> {
+    let comment_id = pick_comment("Edit Comment", comments)?;
+    let current = comments.iter().find(|comment| comment.id == comment_id)?;
+
+    println!("New Text (empty keeps \"{}\"): ", current.body);
+    let body = get_user_input();
+
+    Some((comment_id, keep_if_empty(body, &current.body)))
+}
+
+fn delete_comment_prompt(comments: &[Comment]) -> Option<u32> {
+    let comment_id = pick_comment("Delete Comment", comments)?;
+
+    println!(
+        "Are you sure you want to delete comment #{}? [Y/n]: ",
+        comment_id
+    );
+    get_user_input().eq("Y").then_some(comment_id)
+}
+
+// comments are picked by the number shown in front of them on the story page
+fn pick_comment(title: &str, comments: &[Comment]) -> Option<u32> {
+    println!("----------------------------");
+    let choices = comments
+        .iter()
+        .map(|comment| format!("#{}", comment.id))
+        .join(", ");
+    println!("{} ({}): ", title, choices);
+
+    let input = get_user_input();
+    let comment_id = input.trim_start_matches('#').parse::<u32>().ok()?;
+    comments
+        .iter()
+        .any(|comment| comment.id == comment_id)
+        .then_some(comment_id)
+}
+
+fn add_label_prompt() -> Option<String> {
+    println!("----------------------------");
+    println!("Label (empty cancels): ");
+
+    Some(get_user_input()).filter(|label| !label.is_empty())
+}
+
+fn remove_label_prompt(labels: &[String]) -> Option<String> {
+    println!("----------------------------");
+    let choices = labels
+        .iter()
+        .enumerate()
+        .map(|(index, label)| format!("{} - {}", index + 1, label))
+        .join(", ");
+    println!("Remove Label ({}): ", choices);
+
+    get_user_input()
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| choice.checked_sub(1))
+        .and_then(|index| labels.get(index))
+        .cloned()
+}
+
+fn filter_label_prompt(labels: &[(String, usize)]) -> Option<String> {
+    println!("----------------------------");
+    let choices = labels
+        .iter()
+        .enumerate()
+        .map(|(index, (label, count))| format!("{} - {} ({})", index + 1, label, count))
+        .join(", ");
+    println!("Filter By Label ({}, empty shows everything): ", choices);
+
+    get_user_input()
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| choice.checked_sub(1))
+        .and_then(|index| labels.get(index))
+        .map(|(label, _)| label.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_due_date_should_keep_clear_or_validate() {
+        let current = NaiveDate::from_ymd_opt(2024, 1, 31);
+
+        assert_eq!(parse_due_date("", current), Ok(current));
+        assert_eq!(parse_due_date("-", current), Ok(None));
+        assert_eq!(
+            parse_due_date(" 2024-02-29 ", None),
+            Ok(NaiveDate::from_ymd_opt(2024, 2, 29))
+        );
+        assert!(parse_due_date("2023-02-29", None).is_err());
+        assert!(parse_due_date("31/01/2024", None).is_err());
+    }
+
+    #[test]
+    fn input_to_index_should_only_accept_listed_numbers() {
+        assert_eq!(input_to_index("1", 3), Some(0));
+        assert_eq!(input_to_index("3", 3), Some(2));
+        assert_eq!(input_to_index("0", 3), None);
+        assert_eq!(input_to_index("4", 3), None);
+        assert_eq!(input_to_index("", 3), None);
+    }
 }
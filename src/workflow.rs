@@ -0,0 +1,276 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Status;
+
+// lets features like roll-ups tell unstarted, running and finished work apart without knowing
+// what a team calls its states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Todo,
+    InProgress,
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    pub id: Status,
+    pub name: String,
+    pub category: Category,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workflow {
+    pub initial: Status,
+    pub states: Vec<State>,
+    // state id -> states it may move to. without it every state may move to every other one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transitions: Option<HashMap<Status, Vec<Status>>>,
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        let state = |id: &str, name: &str, category| State {
+            id: Status::new(id),
+            name: name.to_string(),
+            category,
+        };
+
+        Self {
+            initial: Status::new("open"),
+            states: vec![
+                state("open", "OPEN", Category::Todo),
+                state("in_progress", "IN PROGRESS", Category::InProgress),
+                state("resolved", "RESOLVED", Category::Done),
+                state("closed", "CLOSED", Category::Done),
+            ],
+            transitions: None,
+        }
+    }
+}
+
+impl Workflow {
+    pub fn state(&self, id: &Status) -> Option<&State> {
+        self.states.iter().find(|state| &state.id == id)
+    }
+
+    // falls back to the raw id for states the workflow no longer has
+    pub fn name_of(&self, id: &Status) -> String {
+        self.state(id)
+            .map(|state| state.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    // position in the declared order, states the workflow no longer has come last
+    pub fn rank(&self, id: &Status) -> usize {
+        self.states
+            .iter()
+            .position(|state| &state.id == id)
+            .unwrap_or(self.states.len())
+    }
+
+    // states the workflow no longer has belong to no category
+    pub fn category_of(&self, id: &Status) -> Option<Category> {
+        self.state(id).map(|state| state.category)
+    }
+
+    // in the order the states are declared. an item stuck in a state that was removed from the
+    // workflow may move anywhere, so it is never stranded
+    pub fn targets(&self, from: &Status) -> Vec<&State> {
+        let allowed = match (&self.transitions, self.state(from)) {
+            (Some(transitions), Some(_)) => transitions.get(from).cloned().unwrap_or_default(),
+            _ => self.states.iter().map(|state| state.id.clone()).collect(),
+        };
+
+        self.states
+            .iter()
+            .filter(|state| &state.id != from && allowed.contains(&state.id))
+            .collect()
+    }
+
+    pub fn can_transition(&self, from: &Status, to: &Status) -> bool {
+        self.targets(from).iter().any(|state| &state.id == to)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let mut ids = HashSet::new();
+        for state in &self.states {
+            if !ids.insert(&state.id) {
+                return Err(format!("state {} is declared twice", state.id));
+            }
+        }
+
+        if self.state(&self.initial).is_none() {
+            return Err(format!("initial state {} is not declared", self.initial));
+        }
+
+        for (from, targets) in self.transitions.iter().flatten() {
+            for id in std::iter::once(from).chain(targets) {
+                if self.state(id).is_none() {
+                    return Err(format!("transition uses undeclared state {id}"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// how an epic's status follows its stories. both rules are off unless the board turns them on
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RollUp {
+    // an epic may only move to a done state once all of its stories are done
+    #[serde(default)]
+    pub block_done: bool,
+    // an epic that has not started moves to its first in progress state when one of its stories
+    // starts, if its workflow allows that
+    #[serde(default)]
+    pub auto_start: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Workflows {
+    #[serde(default)]
+    pub epic: Workflow,
+    #[serde(default)]
+    pub story: Workflow,
+    #[serde(default)]
+    pub roll_up: RollUp,
+}
+
+impl Workflows {
+    pub fn validate(&self) -> Result<(), String> {
+        self.epic
+            .validate()
+            .map_err(|error| format!("epic workflow: {error}"))?;
+        self.story
+            .validate()
+            .map_err(|error| format!("story workflow: {error}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_workflow_should_allow_any_change() {
+        let workflow = Workflow::default();
+
+        let targets: Vec<_> = workflow
+            .targets(&Status::new("resolved"))
+            .iter()
+            .map(|state| state.id.clone())
+            .collect();
+
+        assert!(workflow.validate().is_ok());
+        assert_eq!(
+            targets,
+            vec![
+                Status::new("open"),
+                Status::new("in_progress"),
+                Status::new("closed")
+            ]
+        );
+    }
+
+    #[test]
+    fn targets_should_follow_transitions_in_declared_order() {
+        let workflow = review_workflow();
+
+        let targets: Vec<_> = workflow
+            .targets(&Status::new("in_progress"))
+            .iter()
+            .map(|state| state.name.as_str())
+            .collect();
+
+        assert_eq!(targets, vec!["In Review", "Blocked"]);
+        assert!(!workflow.can_transition(&Status::new("open"), &Status::new("done")));
+        assert!(workflow.targets(&Status::new("done")).is_empty());
+    }
+
+    #[test]
+    fn targets_should_free_items_in_unknown_states() {
+        let workflow = review_workflow();
+
+        assert_eq!(workflow.targets(&Status::new("closed")).len(), 5);
+        assert_eq!(workflow.name_of(&Status::new("closed")), "closed");
+    }
+
+    #[test]
+    fn validate_should_reject_undeclared_states() {
+        let mut workflow = review_workflow();
+        workflow.initial = Status::new("backlog");
+        assert!(workflow.validate().is_err());
+
+        let mut workflow = review_workflow();
+        workflow
+            .transitions
+            .as_mut()
+            .unwrap()
+            .insert(Status::new("open"), vec![Status::new("wontfix")]);
+        assert!(workflow.validate().is_err());
+
+        let mut workflow = review_workflow();
+        workflow.states.push(workflow.states[0].clone());
+        assert!(workflow.validate().is_err());
+    }
+
+    #[test]
+    fn workflows_should_parse_from_config() {
+        let workflows: Workflows = serde_json::from_str(
+            r#"{
+                "story": {
+                    "initial": "todo",
+                    "states": [
+                        { "id": "todo", "name": "To Do", "category": "todo" },
+                        { "id": "done", "name": "Done", "category": "done" }
+                    ],
+                    "transitions": { "todo": ["done"] }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(workflows.epic, Workflow::default());
+        assert_eq!(workflows.roll_up, RollUp::default());
+        assert_eq!(workflows.story.initial, Status::new("todo"));
+        assert!(workflows.validate().is_ok());
+    }
+
+    // open -> in_progress <-> in_review -> done, anything running can get blocked
+    fn review_workflow() -> Workflow {
+        let state = |id: &str, name: &str, category| State {
+            id: Status::new(id),
+            name: name.to_string(),
+            category,
+        };
+        let transitions = [
+            ("open", vec!["in_progress"]),
+            ("in_progress", vec!["blocked", "in_review"]),
+            ("in_review", vec!["in_progress", "done", "blocked"]),
+            ("blocked", vec!["in_progress"]),
+        ];
+
+        Workflow {
+            initial: Status::new("open"),
+            states: vec![
+                state("open", "Open", Category::Todo),
+                state("in_progress", "In Progress", Category::InProgress),
+                state("in_review", "In Review", Category::InProgress),
+                state("blocked", "Blocked", Category::InProgress),
+                state("done", "Done", Category::Done),
+            ],
+            transitions: Some(
+                transitions
+                    .into_iter()
+                    .map(|(from, to)| {
+                        (Status::new(from), to.into_iter().map(Status::new).collect())
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}